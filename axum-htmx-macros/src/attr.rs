@@ -0,0 +1,422 @@
+//! Parsing of the `#[hx_event]` and `#[serde]` attributes.
+
+use syn::{
+    Attribute, Ident, LitStr, Token, Type, ext::IdentExt, meta::ParseNestedMeta, token::Paren,
+};
+
+/// The `#[hx_event]` attributes of a struct, an enum, or a variant deriving
+/// `HxEvent`.
+#[derive(Default)]
+pub(crate) struct Event {
+    pub(crate) name: Option<LitStr>,
+    pub(crate) export: bool,
+}
+
+impl Event {
+    /// Parses the attributes, accepting `name` on structs and variants, and
+    /// `export` on structs and enums.
+    pub(crate) fn parse(attrs: &[Attribute], named: bool, exported: bool) -> syn::Result<Self> {
+        let mut event = Self::default();
+        let expected = match (named, exported) {
+            (true, true) => "`name` or `export`",
+            (true, false) => "`name`",
+            _ => "`export`",
+        };
+
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("hx_event")) {
+            attr.parse_nested_meta(|meta| {
+                if named && meta.path.is_ident("name") {
+                    event.name = Some(meta.value()?.parse::<LitStr>()?);
+                    Ok(())
+                } else if exported && meta.path.is_ident("export") {
+                    event.export = true;
+                    Ok(())
+                } else {
+                    Err(meta.error(format!("unknown `hx_event` attribute, expected {expected}")))
+                }
+            })?;
+        }
+
+        Ok(event)
+    }
+}
+
+/// Returns the name set with `#[hx_event(name = "...")]`, or the kebab-cased
+/// identifier.
+pub(crate) fn event_name(name: Option<LitStr>, ident: &Ident) -> syn::Result<LitStr> {
+    let name =
+        name.unwrap_or_else(|| LitStr::new(&kebab_case(&ident.unraw().to_string()), ident.span()));
+    let value = name.value();
+
+    if value.is_empty() {
+        return Err(syn::Error::new_spanned(name, "event names cannot be empty"));
+    }
+    if let Some(c) = value.chars().find(|&c| !c.is_ascii_graphic() || c == ',') {
+        return Err(syn::Error::new_spanned(
+            name,
+            format!("invalid character {c:?} in event name, expected visible ASCII other than `,`"),
+        ));
+    }
+
+    Ok(name)
+}
+
+/// Keeps the `#[serde]` attributes of a field, for the generated data struct
+/// of an enum variant.
+pub(crate) fn serde_attrs(attrs: &[Attribute]) -> impl Iterator<Item = &Attribute> {
+    attrs.iter().filter(|attr| attr.path().is_ident("serde"))
+}
+
+/// The `#[serde]` attributes of a struct or enum that change its shape.
+#[derive(Default)]
+pub(crate) struct Container {
+    pub(crate) rename_all: Option<RenameRule>,
+    pub(crate) transparent: bool,
+}
+
+impl Container {
+    pub(crate) fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut container = Self::default();
+
+        parse_serde(attrs, |meta| {
+            if meta.path.is_ident("rename_all") {
+                if let Some(rule) = serialize_name(&meta)? {
+                    container.rename_all = Some(RenameRule::parse(&rule)?);
+                }
+            } else if meta.path.is_ident("transparent") {
+                container.transparent = true;
+            } else {
+                skip(&meta)?;
+            }
+            Ok(())
+        })?;
+
+        Ok(container)
+    }
+}
+
+/// Returns the rule set with `#[serde(rename_all_fields = "...")]` on an enum
+/// deriving `HxEvent`.
+///
+/// Each variant is serialized on its own, so the attributes that only apply to
+/// the enum as a whole are rejected rather than ignored.
+pub(crate) fn enum_rename_all_fields(attrs: &[Attribute]) -> syn::Result<Option<LitStr>> {
+    let mut rename_all_fields = None;
+
+    parse_serde(attrs, |meta| {
+        if meta.path.is_ident("rename_all_fields") {
+            rename_all_fields = serialize_name(&meta)?.or(rename_all_fields.take());
+        } else if meta.path.is_ident("rename_all") {
+            return Err(meta.error(
+                "`rename_all` renames the variants, which does not change the event names, \
+                 use `rename_all_fields` to rename the fields of their data",
+            ));
+        } else if let Some(ident) = ["tag", "content", "untagged", "into", "remote"]
+            .into_iter()
+            .find(|ident| meta.path.is_ident(ident))
+        {
+            return Err(meta.error(format!(
+                "`{ident}` is not supported by `HxEvent`, each variant is serialized on its own"
+            )));
+        } else {
+            skip(&meta)?;
+        }
+        Ok(())
+    })?;
+
+    if let Some(rule) = &rename_all_fields {
+        RenameRule::parse(rule)?;
+    }
+
+    Ok(rename_all_fields)
+}
+
+/// Returns the rule set with `#[serde(rename_all = "...")]` on a variant of an
+/// enum deriving `HxEvent`, rejecting the attributes that cannot be applied
+/// to its data.
+pub(crate) fn variant_rename_all(attrs: &[Attribute]) -> syn::Result<Option<LitStr>> {
+    let mut rename_all = None;
+
+    parse_serde(attrs, |meta| {
+        if meta.path.is_ident("rename_all") {
+            rename_all = serialize_name(&meta)?.or(rename_all.take());
+        } else if meta.path.is_ident("rename") {
+            return Err(meta.error(
+                "`rename` does not change the event name, use `#[hx_event(name = \"...\")]`",
+            ));
+        } else if let Some(ident) = [
+            "skip",
+            "skip_serializing",
+            "with",
+            "serialize_with",
+            "untagged",
+        ]
+        .into_iter()
+        .find(|ident| meta.path.is_ident(ident))
+        {
+            return Err(meta.error(format!(
+                "`{ident}` is not supported on the variants of an `HxEvent`"
+            )));
+        } else {
+            skip(&meta)?;
+        }
+        Ok(())
+    })?;
+
+    if let Some(rule) = &rename_all {
+        RenameRule::parse(rule)?;
+    }
+
+    Ok(rename_all)
+}
+
+/// The `#[serde]` attributes of a field or variant that change its shape, and
+/// the type set with `#[hx_event(shape = Type)]`.
+#[derive(Default)]
+pub(crate) struct Member {
+    pub(crate) rename: Option<LitStr>,
+    pub(crate) skip: bool,
+    pub(crate) optional: bool,
+    pub(crate) flatten: bool,
+    pub(crate) shape: Option<Type>,
+}
+
+impl Member {
+    pub(crate) fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut member = Self::default();
+
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("hx_event")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("shape") {
+                    member.shape = Some(meta.value()?.parse()?);
+                    Ok(())
+                } else {
+                    Err(meta.error("unknown `hx_event` attribute, expected `shape`"))
+                }
+            })?;
+        }
+
+        parse_serde(attrs, |meta| {
+            if meta.path.is_ident("rename") {
+                member.rename = serialize_name(&meta)?.or(member.rename.take());
+            } else if meta.path.is_ident("skip") || meta.path.is_ident("skip_serializing") {
+                member.skip = true;
+            } else if meta.path.is_ident("skip_serializing_if") {
+                member.optional = true;
+                skip(&meta)?;
+            } else if meta.path.is_ident("flatten") {
+                member.flatten = true;
+            } else {
+                skip(&meta)?;
+            }
+            Ok(())
+        })?;
+
+        Ok(member)
+    }
+}
+
+/// Returns `true` if the `#[serde]` attributes only use the `allowed` keys.
+pub(crate) fn serde_keys_within(attrs: &[Attribute], allowed: &[&str]) -> syn::Result<bool> {
+    let mut within = true;
+
+    parse_serde(attrs, |meta| {
+        within &= allowed.iter().any(|key| meta.path.is_ident(key));
+        skip(&meta)
+    })?;
+
+    Ok(within)
+}
+
+fn parse_serde(
+    attrs: &[Attribute],
+    mut f: impl FnMut(ParseNestedMeta) -> syn::Result<()>,
+) -> syn::Result<()> {
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+        attr.parse_nested_meta(&mut f)?;
+    }
+    Ok(())
+}
+
+/// Parses `key = "name"` or `key(serialize = "name")`.
+fn serialize_name(meta: &ParseNestedMeta) -> syn::Result<Option<LitStr>> {
+    if meta.input.peek(Token![=]) {
+        return meta.value()?.parse().map(Some);
+    }
+
+    let mut name = None;
+    meta.parse_nested_meta(|meta| {
+        if meta.path.is_ident("serialize") {
+            name = Some(meta.value()?.parse()?);
+        } else {
+            skip(&meta)?;
+        }
+        Ok(())
+    })?;
+
+    Ok(name)
+}
+
+/// Skips a `#[serde]` attribute that doesn't change the shape.
+fn skip(meta: &ParseNestedMeta) -> syn::Result<()> {
+    if meta.input.peek(Token![=]) {
+        meta.value()?.parse::<syn::Expr>()?;
+    } else if meta.input.peek(Paren) {
+        meta.parse_nested_meta(|meta| skip(&meta))?;
+    }
+    Ok(())
+}
+
+/// The case conversions of `#[serde(rename_all = "...")]`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RenameRule {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+}
+
+impl RenameRule {
+    pub(crate) fn parse(rule: &LitStr) -> syn::Result<Self> {
+        Ok(match rule.value().as_str() {
+            "lowercase" => Self::Lower,
+            "UPPERCASE" => Self::Upper,
+            "PascalCase" => Self::Pascal,
+            "camelCase" => Self::Camel,
+            "snake_case" => Self::Snake,
+            "SCREAMING_SNAKE_CASE" => Self::ScreamingSnake,
+            "kebab-case" => Self::Kebab,
+            "SCREAMING-KEBAB-CASE" => Self::ScreamingKebab,
+            _ => return Err(syn::Error::new_spanned(rule, "unknown `rename_all` rule")),
+        })
+    }
+
+    /// Renames a snake_case field, as serde does.
+    pub(crate) fn apply_to_field(self, field: &str) -> String {
+        match self {
+            Self::Lower | Self::Snake => field.to_owned(),
+            Self::Upper | Self::ScreamingSnake => field.to_ascii_uppercase(),
+            Self::Pascal | Self::Camel => {
+                let mut pascal = String::with_capacity(field.len());
+                let mut capitalize = self == Self::Pascal;
+                for c in field.chars() {
+                    if c == '_' {
+                        capitalize = true;
+                    } else if capitalize {
+                        pascal.push(c.to_ascii_uppercase());
+                        capitalize = false;
+                    } else {
+                        pascal.push(c);
+                    }
+                }
+                pascal
+            }
+            Self::Kebab => field.replace('_', "-"),
+            Self::ScreamingKebab => field.to_ascii_uppercase().replace('_', "-"),
+        }
+    }
+
+    /// Renames a PascalCase variant, as serde does.
+    pub(crate) fn apply_to_variant(self, variant: &str) -> String {
+        match self {
+            Self::Pascal => variant.to_owned(),
+            Self::Lower => variant.to_ascii_lowercase(),
+            Self::Upper => variant.to_ascii_uppercase(),
+            Self::Camel => {
+                let mut chars = variant.chars();
+                chars
+                    .next()
+                    .map(|c| c.to_ascii_lowercase())
+                    .into_iter()
+                    .chain(chars)
+                    .collect()
+            }
+            Self::Snake | Self::ScreamingSnake | Self::Kebab | Self::ScreamingKebab => {
+                let mut snake = String::with_capacity(variant.len() + 4);
+                for (i, c) in variant.char_indices() {
+                    if i > 0 && c.is_uppercase() {
+                        snake.push('_');
+                    }
+                    snake.push(c.to_ascii_lowercase());
+                }
+                match self {
+                    Self::Snake => snake,
+                    Self::ScreamingSnake => snake.to_ascii_uppercase(),
+                    Self::Kebab => snake.replace('_', "-"),
+                    _ => snake.to_ascii_uppercase().replace('_', "-"),
+                }
+            }
+        }
+    }
+}
+
+/// Converts a Rust identifier to kebab-case, keeping acronyms together:
+/// `HTMLLoaded` becomes `html-loaded`.
+pub(crate) fn kebab_case(ident: &str) -> String {
+    let chars = ident.chars().collect::<Vec<_>>();
+    let mut kebab = String::with_capacity(ident.len() + 4);
+
+    for (i, &c) in chars.iter().enumerate() {
+        if c == '_' {
+            if !kebab.is_empty() && !kebab.ends_with('-') {
+                kebab.push('-');
+            }
+            continue;
+        }
+
+        if c.is_uppercase() && i > 0 {
+            let prev = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|c| c.is_lowercase());
+
+            if (prev.is_lowercase()
+                || prev.is_ascii_digit()
+                || (prev.is_uppercase() && next_is_lower))
+                && !kebab.ends_with('-')
+            {
+                kebab.push('-');
+            }
+        }
+
+        kebab.extend(c.to_lowercase());
+    }
+
+    kebab.trim_end_matches('-').to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kebab_case_names() {
+        assert_eq!(kebab_case("CartUpdated"), "cart-updated");
+        assert_eq!(kebab_case("Saved"), "saved");
+        assert_eq!(kebab_case("HTMLLoaded"), "html-loaded");
+        assert_eq!(kebab_case("ItemV2Added"), "item-v2-added");
+        assert_eq!(kebab_case("snake_case_event"), "snake-case-event");
+        assert_eq!(kebab_case("_Private_"), "private");
+    }
+
+    #[test]
+    fn rename_rules() {
+        assert_eq!(RenameRule::Camel.apply_to_field("item_count"), "itemCount");
+        assert_eq!(RenameRule::Pascal.apply_to_field("item_count"), "ItemCount");
+        assert_eq!(RenameRule::Kebab.apply_to_field("item_count"), "item-count");
+        assert_eq!(
+            RenameRule::ScreamingSnake.apply_to_field("item_count"),
+            "ITEM_COUNT"
+        );
+
+        assert_eq!(RenameRule::Lower.apply_to_variant("InStock"), "instock");
+        assert_eq!(RenameRule::Camel.apply_to_variant("InStock"), "inStock");
+        assert_eq!(RenameRule::Snake.apply_to_variant("InStock"), "in_stock");
+        assert_eq!(
+            RenameRule::ScreamingKebab.apply_to_variant("InStock"),
+            "IN-STOCK"
+        );
+    }
+}
@@ -0,0 +1,371 @@
+//! `#[derive(HxEvent)]`.
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{
+    Data, DeriveInput, Fields, GenericArgument, LitStr, Path, PathArguments, Type, parse_quote,
+};
+
+use crate::{
+    attr::{
+        Container, Event, RenameRule, enum_rename_all_fields, event_name, serde_attrs,
+        serde_keys_within, variant_rename_all,
+    },
+    shape::{bound_fields, fields_shape},
+};
+
+pub(crate) fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let ident = &input.ident;
+    let event = Event::parse(&input.attrs, matches!(input.data, Data::Struct(_)), true)?;
+    let mut from_generics = input.generics.clone();
+    let mut types_generics = input.generics.clone();
+    let mut infallible = true;
+
+    let (body, event_types) = match &input.data {
+        Data::Struct(data) => {
+            let name = event_name(event.name, ident)?;
+
+            if let Fields::Unit = data.fields {
+                (event_without_data(&name), vec![event_type(&name, None)])
+            } else {
+                infallible = serde_keys_within(&input.attrs, &INFALLIBLE_CONTAINER_ATTRS)?
+                    && infallible_fields(&data.fields)?;
+                from_generics
+                    .make_where_clause()
+                    .predicates
+                    .push(parse_quote!(Self: ::axum_htmx::__private::serde::Serialize));
+
+                let mut event_types = Vec::new();
+                if event.export {
+                    bound_fields(&mut types_generics, &data.fields)?;
+                    let shape = fields_shape(&data.fields, &Container::parse(&input.attrs)?)?;
+                    event_types.push(event_type(&name, Some(shape)));
+                }
+
+                (event_with_data(&name, quote!(self)), event_types)
+            }
+        }
+        Data::Enum(data) => {
+            if !input.generics.params.is_empty() {
+                return Err(syn::Error::new_spanned(
+                    &input.generics,
+                    "`HxEvent` cannot be derived for generic enums",
+                ));
+            }
+
+            let rename_all_fields = enum_rename_all_fields(&input.attrs)?;
+            let mut arms = Vec::with_capacity(data.variants.len());
+            let mut event_types = Vec::with_capacity(data.variants.len());
+
+            for variant in &data.variants {
+                let name = event_name(
+                    Event::parse(&variant.attrs, true, false)?.name,
+                    &variant.ident,
+                )?;
+                let rename_all = variant_rename_all(&variant.attrs)?.or(rename_all_fields.clone());
+                let container_attr = rename_all
+                    .as_ref()
+                    .map(|rule| quote! { #[serde(rename_all = #rule)] });
+                let fields = &variant.fields;
+                let variant = &variant.ident;
+                infallible &= infallible_fields(fields)?;
+
+                arms.push(match fields {
+                    Fields::Unit => {
+                        let event = event_without_data(&name);
+                        quote! { #ident::#variant => #event }
+                    }
+                    Fields::Named(fields) => {
+                        let names = fields.named.iter().map(|f| &f.ident).collect::<Vec<_>>();
+                        let fields = fields.named.iter().map(|f| {
+                            let (attrs, name, ty) = (serde_attrs(&f.attrs), &f.ident, &f.ty);
+                            quote! { #(#attrs)* #name: #ty }
+                        });
+                        let event = event_with_data(&name, quote!(Data { #(#names),* }));
+
+                        quote! {
+                            #ident::#variant { #(#names),* } => {
+                                #[derive(::axum_htmx::__private::serde::Serialize)]
+                                #[serde(crate = "::axum_htmx::__private::serde")]
+                                #container_attr
+                                struct Data { #(#fields),* }
+
+                                #event
+                            }
+                        }
+                    }
+                    Fields::Unnamed(fields) => {
+                        let names = (0..fields.unnamed.len())
+                            .map(|i| format_ident!("__field{}", i))
+                            .collect::<Vec<_>>();
+                        let fields = fields.unnamed.iter().map(|f| {
+                            let (attrs, ty) = (serde_attrs(&f.attrs), &f.ty);
+                            quote! { #(#attrs)* #ty }
+                        });
+                        let event = event_with_data(&name, quote!(Data(#(#names),*)));
+
+                        quote! {
+                            #ident::#variant(#(#names),*) => {
+                                #[derive(::axum_htmx::__private::serde::Serialize)]
+                                #[serde(crate = "::axum_htmx::__private::serde")]
+                                #container_attr
+                                struct Data(#(#fields),*);
+
+                                #event
+                            }
+                        }
+                    }
+                });
+
+                if event.export {
+                    let shape = match fields {
+                        Fields::Unit => None,
+                        _ => {
+                            let container = Container {
+                                rename_all: rename_all
+                                    .as_ref()
+                                    .map(RenameRule::parse)
+                                    .transpose()?,
+                                ..Container::default()
+                            };
+                            Some(fields_shape(fields, &container)?)
+                        }
+                    };
+                    event_types.push(event_type(&name, shape));
+                }
+            }
+
+            (
+                quote! {
+                    match self {
+                        #(#arms,)*
+                    }
+                },
+                event_types,
+            )
+        }
+        Data::Union(data) => {
+            return Err(syn::Error::new_spanned(
+                data.union_token,
+                "`HxEvent` cannot be derived for unions",
+            ));
+        }
+    };
+
+    let vis = &input.vis;
+    let (impl_generics, ty_generics, where_clause) = from_generics.split_for_impl();
+    let conversion = if infallible {
+        let message = format!("the data of a `{ident}` event always serializes");
+        quote! {
+            impl #impl_generics ::core::convert::From<#ident #ty_generics> for ::axum_htmx::HxEvent
+            #where_clause
+            {
+                fn from(event: #ident #ty_generics) -> Self {
+                    event.try_into_hx_event().expect(#message)
+                }
+            }
+        }
+    } else {
+        quote! {
+            impl #impl_generics ::core::convert::TryFrom<#ident #ty_generics> for ::axum_htmx::HxEvent
+            #where_clause
+            {
+                type Error = ::axum_htmx::__private::serde_json::Error;
+
+                fn try_from(
+                    event: #ident #ty_generics,
+                ) -> ::core::result::Result<Self, Self::Error> {
+                    event.try_into_hx_event()
+                }
+            }
+        }
+    };
+    let from = quote! {
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Converts the event into an `HxEvent`, failing if its data
+            /// cannot be serialized.
+            #vis fn try_into_hx_event(
+                self,
+            ) -> ::core::result::Result<::axum_htmx::HxEvent, ::axum_htmx::__private::serde_json::Error>
+            {
+                #body
+            }
+        }
+
+        #conversion
+    };
+
+    if !event.export {
+        return Ok(from);
+    }
+
+    let (impl_generics, ty_generics, where_clause) = types_generics.split_for_impl();
+
+    Ok(quote! {
+        #from
+
+        impl #impl_generics ::axum_htmx::export::HxEventTypes for #ident #ty_generics
+        #where_clause
+        {
+            fn event_types() -> ::std::vec::Vec<::axum_htmx::export::EventType> {
+                ::std::vec![#(#event_types),*]
+            }
+        }
+    })
+}
+
+fn event_without_data(name: &LitStr) -> TokenStream2 {
+    quote! { ::core::result::Result::Ok(::axum_htmx::HxEvent::new(#name)) }
+}
+
+fn event_with_data(name: &LitStr, data: TokenStream2) -> TokenStream2 {
+    quote! { ::axum_htmx::HxEvent::new_with_data(#name, #data) }
+}
+
+fn event_type(name: &LitStr, shape: Option<TokenStream2>) -> TokenStream2 {
+    let shape = match shape {
+        Some(shape) => quote! { ::core::option::Option::Some(#shape) },
+        None => quote! { ::core::option::Option::None },
+    };
+
+    quote! { ::axum_htmx::export::EventType::new(#name, #shape) }
+}
+
+/// The `#[serde]` attributes of a struct that cannot make it fail to
+/// serialize.
+const INFALLIBLE_CONTAINER_ATTRS: [&str; 12] = [
+    "rename",
+    "rename_all",
+    "rename_all_fields",
+    "deny_unknown_fields",
+    "tag",
+    "bound",
+    "default",
+    "transparent",
+    "from",
+    "try_from",
+    "crate",
+    "expecting",
+];
+
+/// The `#[serde]` attributes of a field that cannot make it fail to serialize.
+const INFALLIBLE_FIELD_ATTRS: [&str; 10] = [
+    "rename",
+    "alias",
+    "default",
+    "skip",
+    "skip_serializing",
+    "skip_serializing_if",
+    "skip_deserializing",
+    "deserialize_with",
+    "borrow",
+    "bound",
+];
+
+/// The types that serialize to JSON strings or numbers, and can be the keys of
+/// a JSON object.
+const KEY_TYPES: [&str; 15] = [
+    "String", "str", "char", "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64",
+    "u128", "usize",
+];
+
+/// The other types that always serialize to JSON.
+const VALUE_TYPES: [&str; 10] = [
+    "bool",
+    "f32",
+    "f64",
+    "Duration",
+    "IpAddr",
+    "Ipv4Addr",
+    "Ipv6Addr",
+    "SocketAddr",
+    "SocketAddrV4",
+    "SocketAddrV6",
+];
+
+/// Returns `true` if the fields are known to serialize to JSON without error,
+/// so that `From` is implemented rather than `TryFrom`.
+fn infallible_fields(fields: &Fields) -> syn::Result<bool> {
+    for field in fields {
+        if !serde_keys_within(&field.attrs, &INFALLIBLE_FIELD_ATTRS)? || !infallible_type(&field.ty)
+        {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
+/// Returns `true` if `ty` is built from primitives, strings, and the standard
+/// collections, which always serialize to JSON, maps having string or integer
+/// keys.
+fn infallible_type(ty: &Type) -> bool {
+    match ty {
+        Type::Array(ty) => infallible_type(&ty.elem),
+        Type::Group(ty) => infallible_type(&ty.elem),
+        Type::Paren(ty) => infallible_type(&ty.elem),
+        Type::Reference(ty) => infallible_type(&ty.elem),
+        Type::Slice(ty) => infallible_type(&ty.elem),
+        Type::Tuple(ty) => ty.elems.iter().all(infallible_type),
+        Type::Path(ty) if ty.qself.is_none() => {
+            let Some((name, args)) = std_type(&ty.path) else {
+                return false;
+            };
+
+            match (name.as_str(), args.as_slice()) {
+                ("BTreeMap", [key, value]) | ("HashMap", [key, value, ..]) => {
+                    map_key(key) && infallible_type(value)
+                }
+                (
+                    "Option" | "Vec" | "VecDeque" | "BTreeSet" | "HashSet" | "Box" | "Rc" | "Arc"
+                    | "Cow",
+                    [ty, ..],
+                ) => infallible_type(ty),
+                (name, []) => KEY_TYPES.contains(&name) || VALUE_TYPES.contains(&name),
+                _ => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+/// Returns `true` if `ty` can be the key of a JSON object.
+fn map_key(ty: &Type) -> bool {
+    match ty {
+        Type::Reference(ty) => map_key(&ty.elem),
+        Type::Path(ty) if ty.qself.is_none() => match std_type(&ty.path) {
+            Some((name, args)) => match (name.as_str(), args.as_slice()) {
+                ("Cow", [ty]) => map_key(ty),
+                (name, []) => KEY_TYPES.contains(&name),
+                _ => false,
+            },
+            None => false,
+        },
+        _ => false,
+    }
+}
+
+/// Returns the name and type arguments of a type that is either named without
+/// a path, or from the standard library.
+fn std_type(path: &Path) -> Option<(String, Vec<&Type>)> {
+    let first = path.segments.first()?;
+    if path.segments.len() > 1 && !["std", "core", "alloc"].iter().any(|c| first.ident == c) {
+        return None;
+    }
+
+    let last = path.segments.last()?;
+    let args = match &last.arguments {
+        PathArguments::None => Vec::new(),
+        PathArguments::AngleBracketed(args) => args
+            .args
+            .iter()
+            .filter_map(|arg| match arg {
+                GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            })
+            .collect(),
+        PathArguments::Parenthesized(_) => return None,
+    };
+
+    Some((last.ident.to_string(), args))
+}
@@ -0,0 +1,39 @@
+//! Derive macros for [`axum-htmx`](https://docs.rs/axum-htmx).
+//!
+//! The macros are re-exported by `axum-htmx` with the `macros` feature, and
+//! should be used from there.
+
+#![forbid(unsafe_code)]
+
+use proc_macro::TokenStream;
+use syn::{DeriveInput, parse_macro_input};
+
+mod attr;
+mod event;
+mod shape;
+
+/// Implements `From<T>` for `axum_htmx::HxEvent` when the data always
+/// serializes, `TryFrom<T>` otherwise, a fallible `try_into_hx_event()`
+/// method, and `axum_htmx::export::HxEventTypes` with `#[hx_event(export)]`.
+///
+/// See the documentation of the re-export in `axum-htmx`.
+#[proc_macro_derive(HxEvent, attributes(hx_event, serde))]
+pub fn derive_hx_event(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    event::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Implements `axum_htmx::export::HxShape`.
+///
+/// See the documentation of the re-export in `axum-htmx`.
+#[proc_macro_derive(HxShape, attributes(hx_event, serde))]
+pub fn derive_hx_shape(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    shape::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
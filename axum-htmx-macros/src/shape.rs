@@ -0,0 +1,178 @@
+//! `#[derive(HxShape)]`, and the shapes of the data of derived events.
+
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::{Data, DeriveInput, Fields, Generics, ext::IdentExt, parse_quote};
+
+use crate::attr::{Container, Member};
+
+pub(crate) fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let ident = &input.ident;
+    let container = Container::parse(&input.attrs)?;
+    let mut generics = input.generics.clone();
+
+    let shape = match &input.data {
+        Data::Struct(data) => {
+            bound_fields(&mut generics, &data.fields)?;
+            fields_shape(&data.fields, &container)?
+        }
+        Data::Enum(data) => {
+            let mut variants = Vec::with_capacity(data.variants.len());
+
+            for variant in &data.variants {
+                if !matches!(variant.fields, Fields::Unit) {
+                    return Err(syn::Error::new_spanned(
+                        variant,
+                        "`HxShape` can only be derived for enums with unit variants",
+                    ));
+                }
+
+                let member = Member::parse(&variant.attrs)?;
+                if member.skip {
+                    continue;
+                }
+
+                variants.push(match member.rename {
+                    Some(name) => name.value(),
+                    None => {
+                        let name = variant.ident.unraw().to_string();
+                        match container.rename_all {
+                            Some(rule) => rule.apply_to_variant(&name),
+                            None => name,
+                        }
+                    }
+                });
+            }
+
+            quote! { ::axum_htmx::export::Shape::Enum(::std::vec![#(#variants),*]) }
+        }
+        Data::Union(data) => {
+            return Err(syn::Error::new_spanned(
+                data.union_token,
+                "`HxShape` cannot be derived for unions",
+            ));
+        }
+    };
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::axum_htmx::export::HxShape for #ident #ty_generics #where_clause {
+            fn shape() -> ::axum_htmx::export::Shape {
+                #shape
+            }
+        }
+    })
+}
+
+/// Requires the fields of a generic type to implement `HxShape`.
+pub(crate) fn bound_fields(generics: &mut Generics, fields: &Fields) -> syn::Result<()> {
+    if generics.params.is_empty() {
+        return Ok(());
+    }
+
+    let where_clause = generics.make_where_clause();
+    for field in fields {
+        let member = Member::parse(&field.attrs)?;
+        if !member.skip {
+            let ty = member.shape.as_ref().unwrap_or(&field.ty);
+            where_clause
+                .predicates
+                .push(parse_quote!(#ty: ::axum_htmx::export::HxShape));
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns an expression building the shape of the fields, as serialized by
+/// serde.
+pub(crate) fn fields_shape(fields: &Fields, container: &Container) -> syn::Result<TokenStream2> {
+    let members = fields
+        .iter()
+        .map(|field| Ok((field, Member::parse(&field.attrs)?)))
+        .collect::<syn::Result<Vec<_>>>()?;
+    let kept = members
+        .iter()
+        .filter(|(_, member)| !member.skip)
+        .collect::<Vec<_>>();
+
+    if container.transparent {
+        let [(field, member)] = kept.as_slice() else {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                "`transparent` structs must have exactly one serialized field",
+            ));
+        };
+        let ty = member.shape.as_ref().unwrap_or(&field.ty);
+
+        return Ok(quote! { <#ty as ::axum_htmx::export::HxShape>::shape() });
+    }
+
+    Ok(match fields {
+        Fields::Unit => quote! { ::axum_htmx::export::Shape::Null },
+        Fields::Unnamed(unnamed) if unnamed.unnamed.len() == 1 => {
+            let (field, member) = &members[0];
+            let ty = member.shape.as_ref().unwrap_or(&field.ty);
+            quote! { <#ty as ::axum_htmx::export::HxShape>::shape() }
+        }
+        Fields::Unnamed(_) => {
+            let tys = kept
+                .iter()
+                .map(|(field, member)| member.shape.as_ref().unwrap_or(&field.ty));
+            quote! {
+                ::axum_htmx::export::Shape::Tuple(::std::vec![
+                    #(<#tys as ::axum_htmx::export::HxShape>::shape()),*
+                ])
+            }
+        }
+        Fields::Named(_) => {
+            let pushes = kept.iter().map(|(field, member)| {
+                let ty = member.shape.as_ref().unwrap_or(&field.ty);
+
+                if member.flatten {
+                    return quote! {
+                        if let ::axum_htmx::export::Shape::Object(flattened) =
+                            <#ty as ::axum_htmx::export::HxShape>::shape()
+                        {
+                            fields.extend(flattened);
+                        }
+                    };
+                }
+
+                let name = match &member.rename {
+                    Some(name) => name.value(),
+                    None => {
+                        let name = field
+                            .ident
+                            .as_ref()
+                            .expect("named field")
+                            .unraw()
+                            .to_string();
+                        match container.rename_all {
+                            Some(rule) => rule.apply_to_field(&name),
+                            None => name,
+                        }
+                    }
+                };
+                let optional = member.optional.then(|| quote!(.optional()));
+
+                quote! {
+                    fields.push(
+                        ::axum_htmx::export::Field::new(
+                            #name,
+                            <#ty as ::axum_htmx::export::HxShape>::shape(),
+                        )
+                        #optional
+                    );
+                }
+            });
+
+            quote! {{
+                let mut fields = ::std::vec::Vec::new();
+                #(#pushes)*
+                ::axum_htmx::export::Shape::Object(fields)
+            }}
+        }
+    })
+}
@@ -0,0 +1,49 @@
+//! Exporting the typings of events declared in Rust
+//!
+//! Don't forget about the feature while running it:
+//! `cargo run --features macros --example export-events -- frontend/src`
+use std::{env, path::PathBuf};
+
+use axum_htmx::{HxEvent, HxShape, export::HxEventRegistry};
+use serde::Serialize;
+
+#[derive(Serialize, HxShape)]
+#[serde(rename_all = "camelCase")]
+struct CartItem {
+    product_id: u32,
+    quantity: u32,
+}
+
+// Triggers `cart-updated`, with the items as `e.detail.items`.
+#[derive(Serialize, HxEvent)]
+#[hx_event(export)]
+struct CartUpdated {
+    items: Vec<CartItem>,
+}
+
+// Each variant is its own event: `saved`, and `toast:error` with a message.
+#[derive(HxEvent)]
+#[hx_event(export)]
+#[allow(dead_code)]
+enum Toast {
+    Saved,
+    #[hx_event(name = "toast:error")]
+    Failed {
+        message: String,
+    },
+}
+
+fn main() {
+    let out = env::args()
+        .nth(1)
+        .map_or_else(|| PathBuf::from("."), PathBuf::from);
+
+    let registry = HxEventRegistry::new()
+        .register::<CartUpdated>()
+        .register::<Toast>();
+
+    registry
+        .write_typescript(out.join("htmx-events.d.ts"))
+        .unwrap();
+    registry.write_json_schemas(out.join("schemas")).unwrap();
+}
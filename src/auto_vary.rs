@@ -2,7 +2,7 @@
 //! [htmx caching issue](https://htmx.org/docs/#caching)
 
 use std::{
-    sync::Arc,
+    sync::{Arc, Mutex},
     task::{Context, Poll},
 };
 
@@ -18,20 +18,32 @@ use http::{
 use tokio::sync::oneshot::{self, Receiver, Sender};
 use tower::{Layer, Service};
 
+#[cfg(doc)]
+use crate::{
+    HxBoosted, HxHeaders, HxHistoryRestoreRequest, HxRequest, HxRequestKind, HxTarget, HxTrigger,
+    HxTriggerName,
+};
 use crate::{
     HxError,
-    headers::{HX_REQUEST_STR, HX_TARGET_STR, HX_TRIGGER_NAME_STR, HX_TRIGGER_STR},
+    headers::{
+        HX_BOOSTED_STR, HX_HISTORY_RESTORE_REQUEST_STR, HX_REQUEST_STR, HX_TARGET_STR,
+        HX_TRIGGER_NAME_STR, HX_TRIGGER_STR,
+    },
 };
-#[cfg(doc)]
-use crate::{HxRequest, HxTarget, HxTrigger, HxTriggerName};
 
 const MIDDLEWARE_DOUBLE_USE: &str =
     "Configuration error: `axum_httpx::vary_middleware` is used twice";
 
 /// Addresses [htmx caching issues](https://htmx.org/docs/#caching)
 /// by automatically adding a corresponding `Vary` header when
-/// [`HxRequest`], [`HxTarget`], [`HxTrigger`], [`HxTriggerName`]
-/// or their combination is used.
+/// [`HxRequest`], [`HxBoosted`], [`HxHistoryRestoreRequest`], [`HxTarget`],
+/// [`HxTrigger`], [`HxTriggerName`] or their combination is used.
+///
+/// [`HxRequestKind`] depends on `HX-Request`, `HX-Boosted` and
+/// `HX-History-Restore-Request`, so all three are added when it is used.
+///
+/// When using [`HxHeaders`], only the values read by the handler are taken
+/// into account.
 #[derive(Clone)]
 pub struct AutoVaryLayer;
 
@@ -42,9 +54,9 @@ pub struct AutoVaryMiddleware<S> {
 }
 
 pub(crate) trait Notifier {
-    fn sender(&mut self) -> Option<Sender<()>>;
+    fn sender(&self) -> Option<Sender<()>>;
 
-    fn notify(&mut self) {
+    fn notify(&self) {
         if let Some(sender) = self.sender() {
             sender.send(()).ok();
         }
@@ -56,17 +68,17 @@ pub(crate) trait Notifier {
 macro_rules! define_notifiers {
     ($($name:ident),*) => {
         $(
-            #[derive(Clone)]
-            pub(crate) struct $name(Option<Arc<Sender<()>>>);
+            #[derive(Debug, Clone)]
+            pub(crate) struct $name(Arc<Mutex<Option<Sender<()>>>>);
 
             impl Notifier for $name {
-                fn sender(&mut self) -> Option<Sender<()>> {
-                    self.0.take().and_then(Arc::into_inner)
+                fn sender(&self) -> Option<Sender<()>> {
+                    self.0.lock().ok().and_then(|mut sender| sender.take())
                 }
 
                 fn insert(extensions: &mut Extensions) -> Receiver<()> {
                     let (tx, rx) = oneshot::channel();
-                    if extensions.insert(Self(Some(Arc::new(tx)))).is_some() {
+                    if extensions.insert(Self(Arc::new(Mutex::new(Some(tx))))).is_some() {
                         panic!("{}", MIDDLEWARE_DOUBLE_USE);
                     }
                     rx
@@ -78,11 +90,74 @@ macro_rules! define_notifiers {
 
 define_notifiers!(
     HxRequestExtracted,
+    HxBoostedExtracted,
+    HxHistoryRestoreRequestExtracted,
     HxTargetExtracted,
     HxTriggerExtracted,
     HxTriggerNameExtracted
 );
 
+/// Notifiers captured by [`HxHeaders`](crate::HxHeaders), so that only the
+/// fields a handler actually reads end up in the `Vary` header.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct HxHeadersNotifiers {
+    request: Option<HxRequestExtracted>,
+    boosted: Option<HxBoostedExtracted>,
+    history_restore_request: Option<HxHistoryRestoreRequestExtracted>,
+    target: Option<HxTargetExtracted>,
+    trigger: Option<HxTriggerExtracted>,
+    trigger_name: Option<HxTriggerNameExtracted>,
+}
+
+impl HxHeadersNotifiers {
+    pub(crate) fn from_extensions(extensions: &Extensions) -> Self {
+        Self {
+            request: extensions.get().cloned(),
+            boosted: extensions.get().cloned(),
+            history_restore_request: extensions.get().cloned(),
+            target: extensions.get().cloned(),
+            trigger: extensions.get().cloned(),
+            trigger_name: extensions.get().cloned(),
+        }
+    }
+
+    pub(crate) fn request(&self) {
+        if let Some(notifier) = &self.request {
+            notifier.notify();
+        }
+    }
+
+    pub(crate) fn boosted(&self) {
+        if let Some(notifier) = &self.boosted {
+            notifier.notify();
+        }
+    }
+
+    pub(crate) fn history_restore_request(&self) {
+        if let Some(notifier) = &self.history_restore_request {
+            notifier.notify();
+        }
+    }
+
+    pub(crate) fn target(&self) {
+        if let Some(notifier) = &self.target {
+            notifier.notify();
+        }
+    }
+
+    pub(crate) fn trigger(&self) {
+        if let Some(notifier) = &self.trigger {
+            notifier.notify();
+        }
+    }
+
+    pub(crate) fn trigger_name(&self) {
+        if let Some(notifier) = &self.trigger_name {
+            notifier.notify();
+        }
+    }
+}
+
 impl<S> Layer<S> for AutoVaryLayer {
     type Service = AutoVaryMiddleware<S>;
 
@@ -108,6 +183,11 @@ where
         let exts = request.extensions_mut();
         let rx_header = [
             (HxRequestExtracted::insert(exts), HX_REQUEST_STR),
+            (HxBoostedExtracted::insert(exts), HX_BOOSTED_STR),
+            (
+                HxHistoryRestoreRequestExtracted::insert(exts),
+                HX_HISTORY_RESTORE_REQUEST_STR,
+            ),
             (HxTargetExtracted::insert(exts), HX_TARGET_STR),
             (HxTriggerExtracted::insert(exts), HX_TRIGGER_STR),
             (HxTriggerNameExtracted::insert(exts), HX_TRIGGER_NAME_STR),
@@ -148,16 +228,21 @@ mod tests {
     use axum::{Router, routing::get};
 
     use super::*;
-    use crate::{HxRequest, HxTarget, HxTrigger, HxTriggerName};
+    use crate::{
+        HxBoosted, HxHeaders, HxRequest, HxRequestKind, HxTarget, HxTrigger, HxTriggerName,
+    };
 
     fn vary_headers(resp: &axum_test::TestResponse) -> Vec<HeaderValue> {
         resp.iter_headers_by_name("vary").cloned().collect()
     }
 
+    #[allow(clippy::unused_unit)]
     fn server() -> axum_test::TestServer {
         let app = Router::new()
             .route("/no-extractors", get(|| async { () }))
             .route("/hx-request", get(|_: HxRequest| async { () }))
+            .route("/hx-boosted", get(|_: HxBoosted| async { () }))
+            .route("/hx-request-kind", get(|_: HxRequestKind| async { () }))
             .route("/hx-target", get(|_: HxTarget| async { () }))
             .route("/hx-trigger", get(|_: HxTrigger| async { () }))
             .route("/hx-trigger-name", get(|_: HxTriggerName| async { () }))
@@ -169,6 +254,14 @@ mod tests {
                 "/multiple-extractors",
                 get(|_: HxRequest, _: HxTarget, _: HxTrigger, _: HxTriggerName| async { () }),
             )
+            .route("/hx-headers-unread", get(|_: HxHeaders| async { () }))
+            .route(
+                "/hx-headers",
+                get(|headers: HxHeaders| async move {
+                    headers.is_htmx();
+                    headers.trigger();
+                }),
+            )
             .layer(AutoVaryLayer);
         axum_test::TestServer::new(app).unwrap()
     }
@@ -226,4 +319,33 @@ mod tests {
             ["hx-request, hx-target, hx-trigger, hx-trigger-name"],
         );
     }
+
+    #[tokio::test]
+    async fn hx_headers_unread() {
+        assert!(vary_headers(&server().get("/hx-headers-unread").await).is_empty());
+    }
+
+    #[tokio::test]
+    async fn hx_headers_only_read_fields() {
+        assert_eq!(
+            vary_headers(&server().get("/hx-headers").await),
+            ["hx-request, hx-trigger"],
+        );
+    }
+
+    #[tokio::test]
+    async fn single_hx_boosted() {
+        assert_eq!(
+            vary_headers(&server().get("/hx-boosted").await),
+            ["hx-boosted"]
+        );
+    }
+
+    #[tokio::test]
+    async fn hx_request_kind() {
+        assert_eq!(
+            vary_headers(&server().get("/hx-request-kind").await),
+            ["hx-request, hx-boosted, hx-history-restore-request"],
+        );
+    }
 }
@@ -0,0 +1,217 @@
+//! Encoding helpers shared by the extractors and responders.
+
+use std::{borrow::Cow, fmt::Write as _};
+
+use http::{HeaderMap, HeaderName, HeaderValue};
+
+/// Decodes the value of an htmx request header.
+///
+/// Browsers send header values as ISO-8859-1, so values that are not valid
+/// UTF-8 are decoded as such. Characters outside of that range make htmx fall
+/// back to sending the value URI encoded, along with a `<name>-URI-AutoEncoded`
+/// header, in which case the value is percent-decoded as UTF-8.
+///
+/// Returns `Err(())` if the value claims to be URI encoded but is not.
+pub(crate) fn decode_header(headers: &HeaderMap, name: &HeaderName) -> Result<Option<String>, ()> {
+    let Some(value) = headers.get(name) else {
+        return Ok(None);
+    };
+
+    decode_header_value(value, is_uri_encoded(headers, name)).map(Some)
+}
+
+/// Returns `true` if htmx sent the `name` header URI encoded.
+pub(crate) fn is_uri_encoded(headers: &HeaderMap, name: &HeaderName) -> bool {
+    // Avoid formatting the header name for the headers sent by htmx.
+    let value = match name.as_str() {
+        "hx-current-url" => headers.get("hx-current-url-uri-autoencoded"),
+        "hx-prompt" => headers.get("hx-prompt-uri-autoencoded"),
+        "hx-target" => headers.get("hx-target-uri-autoencoded"),
+        "hx-trigger" => headers.get("hx-trigger-uri-autoencoded"),
+        "hx-trigger-name" => headers.get("hx-trigger-name-uri-autoencoded"),
+        _ => headers.get(format!("{name}-uri-autoencoded")),
+    };
+
+    value.is_some_and(|value| value.as_bytes().eq_ignore_ascii_case(b"true"))
+}
+
+/// Decodes a single header value, see [`decode_header`].
+pub(crate) fn decode_header_value(value: &HeaderValue, uri_encoded: bool) -> Result<String, ()> {
+    if uri_encoded {
+        return String::from_utf8(percent_decode(value.as_bytes())?).map_err(|_| ());
+    }
+
+    match std::str::from_utf8(value.as_bytes()) {
+        Ok(value) => Ok(value.to_string()),
+        Err(_) => Ok(value.as_bytes().iter().map(|&b| char::from(b)).collect()),
+    }
+}
+
+/// Decodes `%XX` escape sequences, as produced by `encodeURIComponent`.
+pub(crate) fn percent_decode(input: &[u8]) -> Result<Vec<u8>, ()> {
+    let mut output = Vec::with_capacity(input.len());
+    let mut bytes = input.iter();
+
+    while let Some(&byte) = bytes.next() {
+        if byte != b'%' {
+            output.push(byte);
+            continue;
+        }
+
+        let hex = |b: Option<&u8>| b.and_then(|&b| char::from(b).to_digit(16)).ok_or(());
+        let (high, low) = (hex(bytes.next())?, hex(bytes.next())?);
+        output.push((high * 16 + low) as u8);
+    }
+
+    Ok(output)
+}
+
+/// Percent-encodes the bytes of `url` that cannot be sent in a header, i.e.
+/// anything but visible ASCII. Existing escape sequences are kept as-is.
+pub(crate) fn percent_encode(url: &str) -> Cow<'_, str> {
+    if url.bytes().all(|b| b.is_ascii_graphic()) {
+        return Cow::Borrowed(url);
+    }
+
+    let mut output = String::with_capacity(url.len() + 8);
+    for byte in url.bytes() {
+        if byte.is_ascii_graphic() {
+            output.push(char::from(byte));
+        } else {
+            let _ = write!(output, "%{byte:02X}");
+        }
+    }
+
+    Cow::Owned(output)
+}
+
+/// Creates the value of a header holding a url, see [`percent_encode`].
+pub(crate) fn url_header_value(url: &str) -> HeaderValue {
+    HeaderValue::try_from(percent_encode(url).into_owned())
+        .expect("percent-encoded urls are visible ASCII")
+}
+
+/// Escapes the characters of a CSS selector that cannot be sent in a header,
+/// i.e. anything but visible ASCII and spaces, as `\XXXXXX` sequences.
+///
+/// Six hex digits are always written, so that the escapes need no terminating
+/// space, which would split the modifiers of an `hx-swap` value.
+pub(crate) fn css_escape(selector: &str) -> Cow<'_, str> {
+    let sendable = |c: char| c == ' ' || c.is_ascii_graphic();
+    if selector.chars().all(sendable) {
+        return Cow::Borrowed(selector);
+    }
+
+    let mut output = String::with_capacity(selector.len() + 16);
+    for c in selector.chars() {
+        if sendable(c) {
+            output.push(c);
+        } else {
+            let _ = write!(output, "\\{:06x}", c as u32);
+        }
+    }
+
+    Cow::Owned(output)
+}
+
+/// Escapes the characters of a JSON text that cannot be sent in a header,
+/// i.e. non-ASCII characters and `DEL`, as `\uXXXX` sequences, and replaces
+/// line breaks with spaces.
+///
+/// In valid JSON these characters only occur in strings, where the escapes
+/// decode to the same value, or as whitespace between tokens, so the result
+/// is always a valid header value.
+pub(crate) fn ascii_json(json: &str) -> Cow<'_, str> {
+    let sendable = |c: char| c.is_ascii() && !matches!(c, '\x7f' | '\n' | '\r');
+    if json.chars().all(sendable) {
+        return Cow::Borrowed(json);
+    }
+
+    let mut output = String::with_capacity(json.len() + 16);
+    for c in json.chars() {
+        if sendable(c) {
+            output.push(c);
+        } else if let '\n' | '\r' = c {
+            output.push(' ');
+        } else {
+            for unit in c.encode_utf16(&mut [0; 2]) {
+                let _ = write!(output, "\\u{unit:04x}");
+            }
+        }
+    }
+
+    Cow::Owned(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_plain_values() {
+        let value = HeaderValue::from_static("hello");
+        assert_eq!(decode_header_value(&value, false).unwrap(), "hello");
+
+        let value = HeaderValue::from_bytes("Café".as_bytes()).unwrap();
+        assert_eq!(decode_header_value(&value, false).unwrap(), "Café");
+
+        // ISO-8859-1, as sent by browsers for characters up to U+00FF
+        let value = HeaderValue::from_bytes(b"Caf\xe9").unwrap();
+        assert_eq!(decode_header_value(&value, false).unwrap(), "Café");
+    }
+
+    #[test]
+    fn decode_uri_encoded_values() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            crate::HX_PROMPT,
+            HeaderValue::from_static("%E2%9C%93%20done"),
+        );
+        assert_eq!(
+            decode_header(&headers, &crate::HX_PROMPT).unwrap().unwrap(),
+            "%E2%9C%93%20done"
+        );
+
+        headers.insert(
+            "hx-prompt-uri-autoencoded",
+            HeaderValue::from_static("true"),
+        );
+        assert_eq!(
+            decode_header(&headers, &crate::HX_PROMPT).unwrap().unwrap(),
+            "✓ done"
+        );
+
+        headers.insert(crate::HX_PROMPT, HeaderValue::from_static("%E2%9C"));
+        assert!(decode_header(&headers, &crate::HX_PROMPT).is_err());
+
+        headers.insert(crate::HX_PROMPT, HeaderValue::from_static("%zz"));
+        assert!(decode_header(&headers, &crate::HX_PROMPT).is_err());
+    }
+
+    #[test]
+    fn percent_encode_urls() {
+        assert!(matches!(percent_encode("/items?page=2"), Cow::Borrowed(_)));
+        assert_eq!(percent_encode("/projects/café"), "/projects/caf%C3%A9");
+        assert_eq!(percent_encode("/a b/%C3%A9"), "/a%20b/%C3%A9");
+        assert_eq!(url_header_value("/✓"), "/%E2%9C%93");
+    }
+
+    #[test]
+    fn escape_selectors() {
+        assert!(matches!(css_escape("#list > li"), Cow::Borrowed(_)));
+        assert_eq!(css_escape("#café\t"), "#caf\\0000e9\\000009");
+    }
+
+    #[test]
+    fn escape_json() {
+        assert!(matches!(ascii_json(r#"{"a":"b"}"#), Cow::Borrowed(_)));
+        assert_eq!(
+            ascii_json(r#"{"message":"Café saved 🎉"}"#),
+            r#"{"message":"Caf\u00e9 saved \ud83c\udf89"}"#
+        );
+        assert_eq!(
+            ascii_json("{\"a\": 1,\r\n\t\"b\": 2}"),
+            "{\"a\": 1,  \t\"b\": 2}"
+        );
+    }
+}
@@ -2,7 +2,7 @@ use std::{error, fmt};
 
 use axum_core::response::IntoResponse;
 use http::{
-    StatusCode,
+    HeaderName, StatusCode,
     header::{InvalidHeaderValue, MaxSizeReached},
 };
 
@@ -63,3 +63,239 @@ impl IntoResponse for HxError {
         (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()).into_response()
     }
 }
+
+/// Rejection used by the [strict](crate::strict) extractors and by extractors
+/// deserializing htmx request headers.
+///
+/// Responds with `400 Bad Request` and a plain text body describing which
+/// header was at fault, except for values that could not be parsed (e.g. a
+/// malformed `hx-prompt` answer), which respond with `422 Unprocessable
+/// Entity` and the parsing error as the body, so that it can be shown to the
+/// user as-is.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum HxHeaderRejection {
+    /// The request header is not present.
+    Missing(HeaderName),
+    /// The request header is present, but its value could not be decoded.
+    Invalid(HeaderName),
+    /// The request header could not be deserialized into the requested type.
+    FailedToDeserialize {
+        /// The name of the header.
+        name: HeaderName,
+        /// The deserialization error message.
+        message: String,
+    },
+    /// The request header could not be parsed into the requested type.
+    FailedToParse {
+        /// The name of the header.
+        name: HeaderName,
+        /// The parsing error message.
+        message: String,
+    },
+}
+
+impl HxHeaderRejection {
+    /// The name of the header that caused the rejection.
+    pub fn header_name(&self) -> &HeaderName {
+        match self {
+            HxHeaderRejection::Missing(name)
+            | HxHeaderRejection::Invalid(name)
+            | HxHeaderRejection::FailedToDeserialize { name, .. }
+            | HxHeaderRejection::FailedToParse { name, .. } => name,
+        }
+    }
+
+    /// The status code of the rejection response.
+    pub fn status(&self) -> StatusCode {
+        match self {
+            HxHeaderRejection::Missing(_)
+            | HxHeaderRejection::Invalid(_)
+            | HxHeaderRejection::FailedToDeserialize { .. } => StatusCode::BAD_REQUEST,
+            HxHeaderRejection::FailedToParse { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+        }
+    }
+}
+
+impl fmt::Display for HxHeaderRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HxHeaderRejection::Missing(name) => write!(f, "Missing request header `{name}`"),
+            HxHeaderRejection::Invalid(name) => write!(f, "Invalid request header `{name}`"),
+            HxHeaderRejection::FailedToDeserialize { name, message } => {
+                write!(
+                    f,
+                    "Failed to deserialize request header `{name}`: {message}"
+                )
+            }
+            HxHeaderRejection::FailedToParse { message, .. } => write!(f, "{message}"),
+        }
+    }
+}
+
+impl error::Error for HxHeaderRejection {}
+
+impl IntoResponse for HxHeaderRejection {
+    fn into_response(self) -> axum_core::response::Response {
+        (self.status(), self.to_string()).into_response()
+    }
+}
+
+/// Error returned when decoding an htmx response header back into one of the
+/// [responders](crate::responders).
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum HxDecodeError {
+    /// The header value contains characters that are not visible ASCII.
+    InvalidHeaderValue,
+    /// The header value is neither `true` nor `false`.
+    InvalidBool(String),
+    /// The header value is not a valid swap specification.
+    InvalidSwap(SwapParseError),
+    /// The header value is JSON encoded, which can only be decoded with the
+    /// `serde` feature enabled.
+    UnsupportedJson,
+    /// The header value is not a valid JSON object.
+    InvalidJson,
+
+    #[cfg(feature = "serde")]
+    #[cfg_attr(feature = "unstable", doc(cfg(feature = "serde")))]
+    Json(serde_json::Error),
+}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(feature = "unstable", doc(cfg(feature = "serde")))]
+impl From<serde_json::Error> for HxDecodeError {
+    fn from(value: serde_json::Error) -> Self {
+        Self::Json(value)
+    }
+}
+
+impl From<SwapParseError> for HxDecodeError {
+    fn from(value: SwapParseError) -> Self {
+        Self::InvalidSwap(value)
+    }
+}
+
+impl fmt::Display for HxDecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HxDecodeError::InvalidHeaderValue => write!(f, "Invalid header value"),
+            HxDecodeError::InvalidBool(value) => {
+                write!(f, "Expected `true` or `false`, found `{value}`")
+            }
+            HxDecodeError::InvalidSwap(e) => write!(f, "{e}"),
+            HxDecodeError::UnsupportedJson => {
+                write!(f, "JSON header values require the `serde` feature")
+            }
+            HxDecodeError::InvalidJson => write!(f, "Invalid JSON object"),
+            #[cfg(feature = "serde")]
+            HxDecodeError::Json(e) => write!(f, "Invalid JSON: {e}"),
+        }
+    }
+}
+
+impl error::Error for HxDecodeError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            HxDecodeError::InvalidSwap(e) => Some(e),
+            #[cfg(feature = "serde")]
+            HxDecodeError::Json(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Error returned when parsing a [`SwapOption`](crate::SwapOption) or an
+/// `hx-swap` specification into a [`Swap`](crate::Swap).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SwapParseError {
+    /// The value is empty.
+    Empty,
+    /// The value contains more than a single swap style, e.g. modifiers where
+    /// only a [`SwapOption`](crate::SwapOption) is expected.
+    NotASingleStyle(String),
+    /// The swap style contains characters that are not visible ASCII.
+    InvalidStyle(String),
+    /// The modifier is not one of the `hx-swap` modifiers.
+    UnknownModifier(String),
+    /// The value of a modifier is invalid.
+    InvalidModifierValue {
+        /// The name of the modifier, e.g. `settle`.
+        modifier: &'static str,
+        /// The invalid value.
+        value: String,
+        /// A description of the expected values.
+        expected: &'static str,
+    },
+}
+
+impl fmt::Display for SwapParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SwapParseError::Empty => write!(f, "Empty swap style"),
+            SwapParseError::NotASingleStyle(value) => {
+                write!(f, "Expected a single swap style, found `{value}`")
+            }
+            SwapParseError::InvalidStyle(style) => {
+                write!(f, "Invalid character in swap style `{style}`")
+            }
+            SwapParseError::UnknownModifier(modifier) => {
+                write!(f, "Unknown swap modifier `{modifier}`")
+            }
+            SwapParseError::InvalidModifierValue {
+                modifier,
+                value,
+                expected,
+            } => write!(
+                f,
+                "Invalid value `{value}` for swap modifier `{modifier}`, expected {expected}"
+            ),
+        }
+    }
+}
+
+impl error::Error for SwapParseError {}
+
+/// Error returned when building a [`Selector`](crate::Selector).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SelectorError {
+    /// The selector, id, or class name is empty.
+    Empty,
+    /// The selector contains a control character.
+    InvalidCharacter(char),
+    /// The selector has unbalanced brackets, quotes, or escapes.
+    Unbalanced(String),
+    /// A `#` or `.` in the selector is not followed by an id or class name.
+    MissingName(String),
+    /// An extended target of htmx, such as `this`, was given where only a CSS
+    /// selector is expected.
+    Extended(String),
+}
+
+impl fmt::Display for SelectorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SelectorError::Empty => write!(f, "Empty selector"),
+            SelectorError::InvalidCharacter(c) => {
+                write!(f, "Invalid character {c:?} in selector")
+            }
+            SelectorError::Unbalanced(selector) => {
+                write!(
+                    f,
+                    "Unbalanced brackets, quotes, or escapes in selector `{selector}`"
+                )
+            }
+            SelectorError::MissingName(selector) => {
+                write!(f, "Missing id or class name in selector `{selector}`")
+            }
+            SelectorError::Extended(selector) => {
+                write!(f, "Expected a CSS selector, found `{selector}`")
+            }
+        }
+    }
+}
+
+impl error::Error for SelectorError {}
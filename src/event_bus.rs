@@ -0,0 +1,266 @@
+//! A request-scoped event bus, allowing code below the handler to trigger
+//! client-side events.
+
+use std::{
+    fmt,
+    future::Future,
+    mem,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+
+use axum_core::{
+    extract::{FromRequestParts, OptionalFromRequestParts},
+    response::{IntoResponse, Response},
+};
+use futures_core::ready;
+use http::{Request, StatusCode, request::Parts};
+use pin_project_lite::pin_project;
+use tower::{Layer, Service};
+
+use crate::{HxEvent, HxTriggers, TriggerMode};
+
+/// Collects the events pushed through [`HxEvents`] while a request is handled,
+/// and writes them to the `HX-Trigger*` headers of the response.
+///
+/// Events are merged with those of any [`HxResponseTrigger`] returned by the
+/// handler, following the same policy.
+///
+/// ```rust
+/// use axum::{Router, routing::post};
+/// use axum_htmx::{HxEventBusLayer, HxEvents};
+///
+/// fn add_to_cart(events: &HxEvents) {
+///     // ...
+///     events.trigger("cart-updated");
+/// }
+///
+/// async fn checkout(events: HxEvents) -> &'static str {
+///     add_to_cart(&events);
+///     "Added!"
+/// }
+///
+/// let app: Router = Router::new()
+///     .route("/cart", post(checkout))
+///     .layer(HxEventBusLayer);
+/// ```
+///
+/// [`HxResponseTrigger`]: crate::HxResponseTrigger
+#[derive(Debug, Clone, Default)]
+pub struct HxEventBusLayer;
+
+impl<S> Layer<S> for HxEventBusLayer {
+    type Service = HxEventBus<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        HxEventBus { inner }
+    }
+}
+
+/// Tower service for [`HxEventBusLayer`].
+#[derive(Debug, Clone)]
+pub struct HxEventBus<S> {
+    inner: S,
+}
+
+impl<S, B> Service<Request<B>> for HxEventBus<S>
+where
+    S: Service<Request<B>, Response = Response>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = private::ResponseFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<B>) -> Self::Future {
+        let events = HxEvents::new();
+        req.extensions_mut().insert(events.clone());
+
+        private::ResponseFuture {
+            response_future: self.inner.call(req),
+            events,
+        }
+    }
+}
+
+mod private {
+    use super::*;
+
+    pin_project! {
+        pub struct ResponseFuture<F> {
+            #[pin]
+            pub(super) response_future: F,
+            pub(super) events: HxEvents,
+        }
+    }
+
+    impl<F, E> Future for ResponseFuture<F>
+    where
+        F: Future<Output = Result<Response, E>>,
+    {
+        type Output = Result<Response, E>;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let this = self.project();
+            let mut response = ready!(this.response_future.poll(cx))?;
+
+            this.events.take().insert_into(response.headers_mut());
+
+            Poll::Ready(Ok(response))
+        }
+    }
+}
+
+/// A handle to the event bus of the current request, installed by
+/// [`HxEventBusLayer`].
+///
+/// The handle is cheap to clone, and can be passed down to any code that needs
+/// to trigger client-side events. Events pushed after the response has been
+/// sent are dropped.
+///
+/// Rejects the request with `500 Internal Server Error` if the layer is
+/// missing.
+#[derive(Clone)]
+pub struct HxEvents(Arc<Mutex<HxTriggers>>);
+
+impl HxEvents {
+    fn new() -> Self {
+        Self(Arc::new(Mutex::new(HxTriggers::new())))
+    }
+
+    /// Triggers an event as soon as the response is received.
+    pub fn trigger(&self, event: impl Into<HxEvent>) {
+        self.push(TriggerMode::Normal, event);
+    }
+
+    /// Triggers an event after the swap step.
+    pub fn trigger_after_swap(&self, event: impl Into<HxEvent>) {
+        self.push(TriggerMode::AfterSwap, event);
+    }
+
+    /// Triggers an event after the settle step.
+    pub fn trigger_after_settle(&self, event: impl Into<HxEvent>) {
+        self.push(TriggerMode::AfterSettle, event);
+    }
+
+    /// Triggers an event at the given phase.
+    pub fn push(&self, mode: TriggerMode, event: impl Into<HxEvent>) {
+        self.lock().push(mode, event);
+    }
+
+    fn take(&self) -> HxTriggers {
+        mem::take(&mut *self.lock())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HxTriggers> {
+        // The events are still valid if a thread panicked while pushing one.
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl fmt::Debug for HxEvents {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("HxEvents").field(&*self.lock()).finish()
+    }
+}
+
+impl<S> FromRequestParts<S> for HxEvents
+where
+    S: Send + Sync,
+{
+    type Rejection = MissingHxEventBus;
+
+    async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<HxEvents>()
+            .cloned()
+            .ok_or(MissingHxEventBus)
+    }
+}
+
+impl<S> OptionalFromRequestParts<S> for HxEvents
+where
+    S: Send + Sync,
+{
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<Option<Self>, Self::Rejection> {
+        Ok(parts.extensions.get::<HxEvents>().cloned())
+    }
+}
+
+/// Rejection used for [`HxEvents`] when [`HxEventBusLayer`] is missing.
+///
+/// Responds with `500 Internal Server Error`, as this is a configuration error.
+#[derive(Debug)]
+pub struct MissingHxEventBus;
+
+impl fmt::Display for MissingHxEventBus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Missing `HxEventBusLayer`, required by `HxEvents`")
+    }
+}
+
+impl std::error::Error for MissingHxEventBus {}
+
+impl IntoResponse for MissingHxEventBus {
+    fn into_response(self) -> Response {
+        (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{Router, routing::get};
+
+    use super::*;
+    use crate::{HX_TRIGGER, HX_TRIGGER_AFTER_SETTLE, HxResponseTrigger};
+
+    fn deep_service_call(events: &HxEvents) {
+        events.trigger("cart-updated");
+        events.trigger_after_settle("notification");
+    }
+
+    #[tokio::test]
+    async fn collects_events() {
+        let app = Router::new()
+            .route(
+                "/",
+                get(|events: HxEvents| async move {
+                    deep_service_call(&events);
+                    (HxResponseTrigger::normal(["saved"]), "ok")
+                }),
+            )
+            .route("/none", get(|_: HxEvents| async { "ok" }))
+            .layer(HxEventBusLayer);
+        let server = axum_test::TestServer::new(app).unwrap();
+
+        let resp = server.get("/").await;
+        assert_eq!(resp.header(HX_TRIGGER), "saved, cart-updated");
+        assert_eq!(resp.header(HX_TRIGGER_AFTER_SETTLE), "notification");
+
+        let resp = server.get("/none").await;
+        assert!(resp.maybe_header(HX_TRIGGER).is_none());
+    }
+
+    #[tokio::test]
+    async fn missing_layer() {
+        let app = Router::new()
+            .route("/", get(|_: HxEvents| async { "ok" }))
+            .route(
+                "/optional",
+                get(|events: Option<HxEvents>| async move { events.is_some().to_string() }),
+            );
+        let server = axum_test::TestServer::new(app).unwrap();
+
+        let resp = server.get("/").expect_failure().await;
+        resp.assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+
+        server.get("/optional").await.assert_text("false");
+    }
+}
@@ -0,0 +1,678 @@
+//! Exports the events declared with `#[derive(HxEvent)]` as TypeScript
+//! typings and JSON Schemas, so that the listeners of the frontend are checked
+//! against the events sent by the server.
+//!
+//! Exporting is opt-in: only the types marked with `#[hx_event(export)]`
+//! implement [`HxEventTypes`], and require the types of their fields to
+//! implement [`HxShape`].
+//!
+//! Event types are registered on an [`HxEventRegistry`], usually from a test
+//! or a small binary of the application, as build scripts cannot use the types
+//! of the crate they build.
+//!
+//! ```rust
+//! use axum_htmx::{HxEvent, HxShape, export::HxEventRegistry};
+//! use serde::Serialize;
+//!
+//! #[derive(Serialize, HxShape)]
+//! struct Item {
+//!     id: u32,
+//!     name: String,
+//! }
+//!
+//! #[derive(Serialize, HxEvent)]
+//! #[hx_event(export)]
+//! struct CartUpdated {
+//!     items: Vec<Item>,
+//! }
+//!
+//! let registry = HxEventRegistry::new().register::<CartUpdated>();
+//!
+//! assert!(registry.to_typescript().contains(
+//!     r#""cart-updated": CustomEvent<CartUpdatedDetail>;"#
+//! ));
+//! # let dir = std::env::temp_dir().join("axum-htmx-export-doc");
+//! registry.write_typescript(dir.join("htmx-events.d.ts")).unwrap();
+//! registry.write_json_schemas(dir.join("schemas")).unwrap();
+//! ```
+//!
+//! The typings add the events to the event maps of the DOM, so that
+//! `document.body.addEventListener("cart-updated", (e) => e.detail.items)` is
+//! type checked. As htmx does, data that isn't an object is exposed as
+//! `e.detail.value`.
+
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+    fmt::Write as _,
+    fs, io,
+    path::Path,
+    rc::Rc,
+    sync::Arc,
+};
+
+use serde_json::{Map, Value, json};
+
+/// The JSON shape of a type, as serialized by `serde_json`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Shape {
+    /// Any JSON value.
+    Any,
+    Null,
+    Bool,
+    Integer,
+    Number,
+    String,
+    /// One of the given strings, such as a unit-only enum.
+    Enum(Vec<&'static str>),
+    /// The value, or `null`.
+    Optional(Box<Shape>),
+    Array(Box<Shape>),
+    Tuple(Vec<Shape>),
+    /// An object with arbitrary keys, and values of the given shape.
+    Map(Box<Shape>),
+    Object(Vec<Field>),
+}
+
+/// A field of a [`Shape::Object`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Field {
+    pub name: &'static str,
+    pub shape: Shape,
+    /// Whether the field may be missing, as with `skip_serializing_if`.
+    pub optional: bool,
+}
+
+impl Field {
+    /// Creates a required field.
+    pub fn new(name: &'static str, shape: Shape) -> Self {
+        Self {
+            name,
+            shape,
+            optional: false,
+        }
+    }
+
+    /// Marks the field as possibly missing.
+    pub fn optional(mut self) -> Self {
+        self.optional = true;
+        self
+    }
+}
+
+/// A type with a known JSON shape, used as event data.
+///
+/// Implemented for the common standard library types, and for custom types
+/// with `#[derive(HxShape)]`. Recursive types are not supported.
+pub trait HxShape {
+    /// Returns the shape of the serialized type.
+    fn shape() -> Shape;
+}
+
+/// An event declared on the server, and the shape of its data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct EventType {
+    pub name: &'static str,
+    /// The shape of the data, or `None` if the event has no data.
+    pub data: Option<Shape>,
+}
+
+impl EventType {
+    /// Creates a new event type.
+    pub fn new(name: &'static str, data: Option<Shape>) -> Self {
+        Self { name, data }
+    }
+}
+
+/// A type converting into one or more events, implemented by
+/// `#[derive(HxEvent)]` for the types marked with `#[hx_event(export)]`.
+pub trait HxEventTypes {
+    /// Returns the events of the type, one for a struct, and one per variant
+    /// for an enum.
+    fn event_types() -> Vec<EventType>;
+}
+
+/// A set of event types, exported as TypeScript typings and JSON Schemas.
+#[derive(Debug, Clone, Default)]
+pub struct HxEventRegistry {
+    events: Vec<EventType>,
+}
+
+impl HxEventRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the events of `T`. Events whose name is already registered are
+    /// ignored.
+    pub fn register<T: HxEventTypes>(mut self) -> Self {
+        for event in T::event_types() {
+            if !self.events.iter().any(|e| e.name == event.name) {
+                self.events.push(event);
+            }
+        }
+        self
+    }
+
+    /// Returns the registered events, in registration order.
+    pub fn events(&self) -> &[EventType] {
+        &self.events
+    }
+
+    /// Returns a TypeScript declaration file, declaring the `CustomEvent` of
+    /// every registered event in the event maps of the DOM.
+    pub fn to_typescript(&self) -> String {
+        let mut ts = String::from(
+            "// Generated by axum-htmx, do not edit.\n\n\
+             /** The `detail` of an event triggered by an `HX-Trigger*` response header. */\n\
+             export type HxEventDetail<T> = T & { elt: Element };\n",
+        );
+
+        let mut names = Vec::with_capacity(self.events.len());
+        for event in &self.events {
+            let mut name = pascal_case(event.name) + "Detail";
+            if names.contains(&name) {
+                name += &names.len().to_string();
+            }
+
+            let detail = match &event.data {
+                None => "{}".to_owned(),
+                Some(data @ (Shape::Object(_) | Shape::Map(_))) => typescript(data),
+                Some(data) => format!("{{ value: {} }}", typescript(data)),
+            };
+
+            let _ = write!(
+                ts,
+                "\n/** The detail of the `{}` event. */\nexport type {name} = HxEventDetail<{detail}>;\n",
+                event.name,
+            );
+            names.push(name);
+        }
+
+        ts.push_str("\nexport interface HxEventMap {\n");
+        for (event, name) in self.events.iter().zip(&names) {
+            let _ = writeln!(ts, "  {}: CustomEvent<{name}>;", json!(event.name));
+        }
+        ts.push_str(
+            "}\n\n\
+             declare global {\n  \
+             interface HTMLElementEventMap extends HxEventMap {}\n  \
+             interface DocumentEventMap extends HxEventMap {}\n\
+             }\n",
+        );
+
+        ts
+    }
+
+    /// Returns a JSON Schema for the data of every registered event that has
+    /// data, along with the name of the event.
+    pub fn to_json_schemas(&self) -> Vec<(&'static str, Value)> {
+        self.events
+            .iter()
+            .filter_map(|event| {
+                let mut schema = json!({
+                    "$schema": "https://json-schema.org/draft/2020-12/schema",
+                    "title": event.name,
+                });
+                schema
+                    .as_object_mut()
+                    .expect("schema is an object")
+                    .extend(json_schema(event.data.as_ref()?));
+
+                Some((event.name, schema))
+            })
+            .collect()
+    }
+
+    /// Writes the output of [`to_typescript`](Self::to_typescript) to `path`.
+    pub fn write_typescript(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(path, self.to_typescript())
+    }
+
+    /// Writes the output of [`to_json_schemas`](Self::to_json_schemas) to
+    /// `dir`, as one `<event>.schema.json` file per event. Characters of the
+    /// event name that aren't alphanumeric, `-`, `_`, or `.` are replaced by
+    /// `_`.
+    pub fn write_json_schemas(&self, dir: impl AsRef<Path>) -> io::Result<()> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+
+        for (name, schema) in self.to_json_schemas() {
+            let file: String = name
+                .chars()
+                .map(|c| match c {
+                    'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' | '.' => c,
+                    _ => '_',
+                })
+                .collect();
+            let json = serde_json::to_string_pretty(&schema).map_err(io::Error::other)?;
+
+            fs::write(dir.join(format!("{file}.schema.json")), json + "\n")?;
+        }
+
+        Ok(())
+    }
+}
+
+fn pascal_case(name: &str) -> String {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .flat_map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|c| c.to_ascii_uppercase())
+                .into_iter()
+                .chain(chars)
+        })
+        .collect()
+}
+
+fn typescript(shape: &Shape) -> String {
+    match shape {
+        Shape::Any => "unknown".to_owned(),
+        Shape::Null => "null".to_owned(),
+        Shape::Bool => "boolean".to_owned(),
+        Shape::Integer | Shape::Number => "number".to_owned(),
+        Shape::String => "string".to_owned(),
+        Shape::Enum(variants) if variants.is_empty() => "never".to_owned(),
+        Shape::Enum(variants) => variants
+            .iter()
+            .map(|v| json!(v).to_string())
+            .collect::<Vec<_>>()
+            .join(" | "),
+        Shape::Optional(shape) => format!("{} | null", typescript(shape)),
+        Shape::Array(shape) => match **shape {
+            Shape::Optional(_) | Shape::Enum(_) => format!("({})[]", typescript(shape)),
+            _ => format!("{}[]", typescript(shape)),
+        },
+        Shape::Tuple(shapes) => format!(
+            "[{}]",
+            shapes.iter().map(typescript).collect::<Vec<_>>().join(", ")
+        ),
+        Shape::Map(shape) => format!("Record<string, {}>", typescript(shape)),
+        Shape::Object(fields) if fields.is_empty() => "{}".to_owned(),
+        Shape::Object(fields) => {
+            let fields = fields
+                .iter()
+                .map(|field| {
+                    let is_ident = field.name.chars().enumerate().all(|(i, c)| {
+                        c == '_'
+                            || c == '$'
+                            || c.is_ascii_alphabetic()
+                            || (i > 0 && c.is_ascii_digit())
+                    });
+                    let name = if is_ident && !field.name.is_empty() {
+                        field.name.to_owned()
+                    } else {
+                        json!(field.name).to_string()
+                    };
+                    let optional = if field.optional { "?" } else { "" };
+
+                    format!("{name}{optional}: {}", typescript(&field.shape))
+                })
+                .collect::<Vec<_>>();
+
+            format!("{{ {} }}", fields.join("; "))
+        }
+    }
+}
+
+fn json_schema(shape: &Shape) -> Map<String, Value> {
+    let schema = match shape {
+        Shape::Any => json!({}),
+        Shape::Null => json!({ "type": "null" }),
+        Shape::Bool => json!({ "type": "boolean" }),
+        Shape::Integer => json!({ "type": "integer" }),
+        Shape::Number => json!({ "type": "number" }),
+        Shape::String => json!({ "type": "string" }),
+        Shape::Enum(variants) => json!({ "enum": variants }),
+        Shape::Optional(shape) => json!({
+            "anyOf": [json_schema(shape), { "type": "null" }],
+        }),
+        Shape::Array(shape) => json!({ "type": "array", "items": json_schema(shape) }),
+        Shape::Tuple(shapes) => json!({
+            "type": "array",
+            "prefixItems": shapes.iter().map(json_schema).collect::<Vec<_>>(),
+            "minItems": shapes.len(),
+            "maxItems": shapes.len(),
+        }),
+        Shape::Map(shape) => json!({
+            "type": "object",
+            "additionalProperties": json_schema(shape),
+        }),
+        Shape::Object(fields) => json!({
+            "type": "object",
+            "properties": fields
+                .iter()
+                .map(|field| (field.name.to_owned(), Value::Object(json_schema(&field.shape))))
+                .collect::<Map<_, _>>(),
+            "required": fields
+                .iter()
+                .filter(|field| !field.optional)
+                .map(|field| field.name)
+                .collect::<Vec<_>>(),
+        }),
+    };
+
+    match schema {
+        Value::Object(schema) => schema,
+        _ => unreachable!("schemas are objects"),
+    }
+}
+
+macro_rules! impl_shape {
+    ($shape:expr => $($ty:ty),+) => {
+        $(
+            impl HxShape for $ty {
+                fn shape() -> Shape {
+                    $shape
+                }
+            }
+        )+
+    };
+}
+
+impl_shape!(Shape::Null => ());
+impl_shape!(Shape::Bool => bool);
+impl_shape!(Shape::Integer => i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+impl_shape!(Shape::Number => f32, f64);
+impl_shape!(Shape::String => char, str, String);
+impl_shape!(Shape::Any => Value, serde_json::value::RawValue, crate::JsonValue, crate::RawJson);
+
+macro_rules! impl_shape_wrapper {
+    ($($ty:ident),+) => {
+        $(
+            impl<T: HxShape + ?Sized> HxShape for $ty<T> {
+                fn shape() -> Shape {
+                    T::shape()
+                }
+            }
+        )+
+    };
+}
+
+impl_shape_wrapper!(Box, Rc, Arc);
+
+impl<T: HxShape + ?Sized> HxShape for &T {
+    fn shape() -> Shape {
+        T::shape()
+    }
+}
+
+impl<T: HxShape + ToOwned + ?Sized> HxShape for Cow<'_, T> {
+    fn shape() -> Shape {
+        T::shape()
+    }
+}
+
+impl<T: HxShape> HxShape for Option<T> {
+    fn shape() -> Shape {
+        Shape::Optional(Box::new(T::shape()))
+    }
+}
+
+macro_rules! impl_shape_seq {
+    ($($ty:ident),+) => {
+        $(
+            impl<T: HxShape> HxShape for $ty<T> {
+                fn shape() -> Shape {
+                    Shape::Array(Box::new(T::shape()))
+                }
+            }
+        )+
+    };
+}
+
+impl_shape_seq!(Vec, VecDeque, BTreeSet);
+
+impl<T: HxShape, S> HxShape for HashSet<T, S> {
+    fn shape() -> Shape {
+        Shape::Array(Box::new(T::shape()))
+    }
+}
+
+impl<T: HxShape> HxShape for [T] {
+    fn shape() -> Shape {
+        Shape::Array(Box::new(T::shape()))
+    }
+}
+
+impl<T: HxShape, const N: usize> HxShape for [T; N] {
+    fn shape() -> Shape {
+        Shape::Array(Box::new(T::shape()))
+    }
+}
+
+impl<K, V: HxShape> HxShape for BTreeMap<K, V> {
+    fn shape() -> Shape {
+        Shape::Map(Box::new(V::shape()))
+    }
+}
+
+impl<K, V: HxShape, S> HxShape for HashMap<K, V, S> {
+    fn shape() -> Shape {
+        Shape::Map(Box::new(V::shape()))
+    }
+}
+
+macro_rules! impl_shape_tuple {
+    ($($ty:ident),+) => {
+        impl<$($ty: HxShape),+> HxShape for ($($ty,)+) {
+            fn shape() -> Shape {
+                Shape::Tuple(vec![$($ty::shape()),+])
+            }
+        }
+    };
+}
+
+impl_shape_tuple!(A);
+impl_shape_tuple!(A, B);
+impl_shape_tuple!(A, B, C);
+impl_shape_tuple!(A, B, C, D);
+impl_shape_tuple!(A, B, C, D, E);
+impl_shape_tuple!(A, B, C, D, E, F);
+
+#[cfg(test)]
+mod tests {
+    use serde::Serialize;
+
+    use super::*;
+    use crate::{HxEvent, HxShape};
+
+    #[derive(Serialize, HxShape)]
+    #[serde(rename_all = "camelCase")]
+    struct Item {
+        item_id: u32,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        label: Option<String>,
+        #[serde(skip)]
+        #[allow(dead_code)]
+        cache: Vec<u8>,
+    }
+
+    #[derive(Serialize, HxShape)]
+    #[serde(rename_all = "lowercase")]
+    #[allow(dead_code)]
+    enum Level {
+        Info,
+        #[serde(rename = "warn")]
+        Warning,
+    }
+
+    #[derive(Serialize, HxEvent)]
+    #[hx_event(export)]
+    struct CartUpdated {
+        items: Vec<Item>,
+        level: Level,
+    }
+
+    #[derive(HxEvent)]
+    #[hx_event(export)]
+    #[allow(dead_code)]
+    enum Toast {
+        Saved,
+        #[hx_event(name = "toast:error")]
+        Failed {
+            message: String,
+        },
+        Progress(u8),
+    }
+
+    #[test]
+    fn event_types() {
+        let registry = HxEventRegistry::new()
+            .register::<CartUpdated>()
+            .register::<Toast>()
+            .register::<CartUpdated>();
+
+        let item = Shape::Object(vec![
+            Field::new("itemId", Shape::Integer),
+            Field::new("label", Shape::Optional(Box::new(Shape::String))).optional(),
+        ]);
+        assert_eq!(
+            registry.events(),
+            [
+                EventType::new(
+                    "cart-updated",
+                    Some(Shape::Object(vec![
+                        Field::new("items", Shape::Array(Box::new(item))),
+                        Field::new("level", Shape::Enum(vec!["info", "warn"])),
+                    ]))
+                ),
+                EventType::new("saved", None),
+                EventType::new(
+                    "toast:error",
+                    Some(Shape::Object(vec![Field::new("message", Shape::String)]))
+                ),
+                EventType::new("progress", Some(Shape::Integer)),
+            ]
+        );
+    }
+
+    #[test]
+    fn renamed_variant_fields() {
+        #[derive(HxEvent)]
+        #[hx_event(export)]
+        #[serde(rename_all_fields = "camelCase")]
+        #[allow(dead_code)]
+        enum Cart {
+            Updated {
+                item_count: u32,
+            },
+            #[serde(rename_all = "kebab-case")]
+            Emptied {
+                item_count: u32,
+            },
+        }
+
+        assert_eq!(
+            Cart::event_types(),
+            [
+                EventType::new(
+                    "updated",
+                    Some(Shape::Object(vec![Field::new("itemCount", Shape::Integer)]))
+                ),
+                EventType::new(
+                    "emptied",
+                    Some(Shape::Object(vec![Field::new(
+                        "item-count",
+                        Shape::Integer
+                    )]))
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn typescript_typings() {
+        let registry = HxEventRegistry::new()
+            .register::<CartUpdated>()
+            .register::<Toast>();
+
+        assert_eq!(
+            registry.to_typescript(),
+            r#"// Generated by axum-htmx, do not edit.
+
+/** The `detail` of an event triggered by an `HX-Trigger*` response header. */
+export type HxEventDetail<T> = T & { elt: Element };
+
+/** The detail of the `cart-updated` event. */
+export type CartUpdatedDetail = HxEventDetail<{ items: { itemId: number; label?: string | null }[]; level: "info" | "warn" }>;
+
+/** The detail of the `saved` event. */
+export type SavedDetail = HxEventDetail<{}>;
+
+/** The detail of the `toast:error` event. */
+export type ToastErrorDetail = HxEventDetail<{ message: string }>;
+
+/** The detail of the `progress` event. */
+export type ProgressDetail = HxEventDetail<{ value: number }>;
+
+export interface HxEventMap {
+  "cart-updated": CustomEvent<CartUpdatedDetail>;
+  "saved": CustomEvent<SavedDetail>;
+  "toast:error": CustomEvent<ToastErrorDetail>;
+  "progress": CustomEvent<ProgressDetail>;
+}
+
+declare global {
+  interface HTMLElementEventMap extends HxEventMap {}
+  interface DocumentEventMap extends HxEventMap {}
+}
+"#
+        );
+    }
+
+    #[test]
+    fn json_schemas() {
+        let schemas = HxEventRegistry::new()
+            .register::<CartUpdated>()
+            .register::<Toast>()
+            .to_json_schemas();
+
+        assert_eq!(
+            schemas.iter().map(|(name, _)| *name).collect::<Vec<_>>(),
+            ["cart-updated", "toast:error", "progress"]
+        );
+        assert_eq!(
+            schemas[0].1,
+            json!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "title": "cart-updated",
+                "type": "object",
+                "properties": {
+                    "items": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "itemId": { "type": "integer" },
+                                "label": { "anyOf": [{ "type": "string" }, { "type": "null" }] },
+                            },
+                            "required": ["itemId"],
+                        },
+                    },
+                    "level": { "enum": ["info", "warn"] },
+                },
+                "required": ["items", "level"],
+            })
+        );
+        assert_eq!(
+            schemas[2].1,
+            json!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "title": "progress",
+                "type": "integer",
+            })
+        );
+    }
+}
@@ -5,9 +5,22 @@ use http::request::Parts;
 
 use crate::{
     HX_BOOSTED, HX_CURRENT_URL, HX_HISTORY_RESTORE_REQUEST, HX_PROMPT, HX_REQUEST, HX_TARGET,
-    HX_TRIGGER, HX_TRIGGER_NAME,
+    HX_TRIGGER, HX_TRIGGER_NAME, encoding::decode_header,
 };
 
+mod aggregate;
+pub use aggregate::*;
+mod current_url;
+#[cfg(feature = "serde")]
+pub use current_url::HxCurrentQuery;
+mod kind;
+pub use kind::*;
+mod prompt;
+pub use prompt::*;
+pub mod strict;
+mod string;
+pub use string::*;
+
 /// The `HX-Boosted` header.
 ///
 /// This is set when a request is made from an element where its parent has the
@@ -27,6 +40,12 @@ where
     type Rejection = std::convert::Infallible;
 
     async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<Self, Self::Rejection> {
+        #[cfg(feature = "auto-vary")]
+        parts
+            .extensions
+            .get::<crate::auto_vary::HxBoostedExtracted>()
+            .map(crate::auto_vary::Notifier::notify);
+
         if parts.headers.contains_key(HX_BOOSTED) {
             Ok(HxBoosted(true))
         } else {
@@ -41,10 +60,54 @@ where
 /// just contains the current url.
 ///
 /// This extractor will always return a value. If the header is not present, or
-/// extractor fails to parse the url it will return `None`.
+/// extractor fails to parse the url it will return `None`. Use
+/// [`strict::HxCurrentUrl`] to reject such requests instead, or to check whether
+/// the url is on the same origin as the request.
 #[derive(Debug, Clone)]
 pub struct HxCurrentUrl(pub Option<http::Uri>);
 
+impl HxCurrentUrl {
+    /// Returns the path of the current url.
+    pub fn path(&self) -> Option<&str> {
+        self.0.as_ref().map(http::Uri::path)
+    }
+
+    /// Returns the query string of the current url, if any.
+    pub fn query(&self) -> Option<&str> {
+        self.0.as_ref().and_then(http::Uri::query)
+    }
+
+    /// Returns the origin (`scheme://host[:port]`) of the current url, if it is
+    /// absolute.
+    pub fn origin(&self) -> Option<String> {
+        self.0.as_ref().and_then(current_url::origin)
+    }
+
+    /// Returns `true` if the path of the current url matches an axum-style
+    /// route template, such as `/projects/{id}/tasks`.
+    pub fn matches_route(&self, route: &str) -> bool {
+        self.path()
+            .is_some_and(|path| current_url::match_route(route, path).is_some())
+    }
+
+    /// Deserializes the segments captured by an axum-style route template from
+    /// the current url.
+    ///
+    /// See [`strict::HxCurrentUrl::path_params`] for more information. Returns
+    /// `Ok(None)` if the header is not present.
+    #[cfg(feature = "serde")]
+    #[cfg_attr(feature = "unstable", doc(cfg(feature = "serde")))]
+    pub fn path_params<T: serde::de::DeserializeOwned>(
+        &self,
+        route: &str,
+    ) -> Result<Option<T>, crate::HxHeaderRejection> {
+        match &self.0 {
+            Some(uri) => current_url::path_params(uri, route),
+            None => Ok(None),
+        }
+    }
+}
+
 impl<S> FromRequestParts<S> for HxCurrentUrl
 where
     S: Send + Sync,
@@ -79,6 +142,12 @@ where
     type Rejection = std::convert::Infallible;
 
     async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<Self, Self::Rejection> {
+        #[cfg(feature = "auto-vary")]
+        parts
+            .extensions
+            .get::<crate::auto_vary::HxHistoryRestoreRequestExtracted>()
+            .map(crate::auto_vary::Notifier::notify);
+
         if parts.headers.contains_key(HX_HISTORY_RESTORE_REQUEST) {
             Ok(HxHistoryRestoreRequest(true))
         } else {
@@ -92,8 +161,13 @@ where
 /// This is set when a request is made from an element that has the `hx-prompt`
 /// attribute set. The value will contain the string input by the user.
 ///
-/// This extractor will always return a value. If the header is not present, it
-/// will return `None`.
+/// Non-ASCII values are decoded as UTF-8, falling back to ISO-8859-1, and
+/// values URI encoded by htmx are percent-decoded. The received value is
+/// available through `raw()` on the [strict] variant.
+///
+/// This extractor will always return a value. If the header is not present, or
+/// cannot be decoded, it will return `None`. Use [`HxPromptParsed`] to parse the
+/// value into another type.
 #[derive(Debug, Clone)]
 pub struct HxPrompt(pub Option<String>);
 
@@ -104,13 +178,9 @@ where
     type Rejection = std::convert::Infallible;
 
     async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<Self, Self::Rejection> {
-        if let Some(prompt) = parts.headers.get(HX_PROMPT) {
-            if let Ok(prompt) = prompt.to_str() {
-                return Ok(HxPrompt(Some(prompt.to_string())));
-            }
-        }
+        let prompt = decode_header(&parts.headers, &HX_PROMPT).ok().flatten();
 
-        Ok(HxPrompt(None))
+        Ok(HxPrompt(prompt))
     }
 }
 
@@ -134,7 +204,7 @@ where
         #[cfg(feature = "auto-vary")]
         parts
             .extensions
-            .get_mut::<crate::auto_vary::HxRequestExtracted>()
+            .get::<crate::auto_vary::HxRequestExtracted>()
             .map(crate::auto_vary::Notifier::notify);
 
         if parts.headers.contains_key(HX_REQUEST) {
@@ -151,8 +221,12 @@ where
 /// attribute set. The value will contain the target element's id. If the id
 /// does not exist on the page, the value will be None.
 ///
-/// This extractor will always return a value. If the header is not present, it
-/// will return `None`.
+/// Non-ASCII values are decoded as UTF-8, falling back to ISO-8859-1, and
+/// values URI encoded by htmx are percent-decoded. The received value is
+/// available through `raw()` on the [strict] variant.
+///
+/// This extractor will always return a value. If the header is not present, or
+/// cannot be decoded, it will return `None`.
 #[derive(Debug, Clone)]
 pub struct HxTarget(pub Option<String>);
 
@@ -166,16 +240,12 @@ where
         #[cfg(feature = "auto-vary")]
         parts
             .extensions
-            .get_mut::<crate::auto_vary::HxTargetExtracted>()
+            .get::<crate::auto_vary::HxTargetExtracted>()
             .map(crate::auto_vary::Notifier::notify);
 
-        if let Some(target) = parts.headers.get(HX_TARGET) {
-            if let Ok(target) = target.to_str() {
-                return Ok(HxTarget(Some(target.to_string())));
-            }
-        }
+        let target = decode_header(&parts.headers, &HX_TARGET).ok().flatten();
 
-        Ok(HxTarget(None))
+        Ok(HxTarget(target))
     }
 }
 
@@ -185,8 +255,12 @@ where
 /// attribute set. The value will contain the trigger element's name. If the
 /// name does not exist on the page, the value will be None.
 ///
-/// This extractor will always return a value. If the header is not present, it
-/// will return `None`.
+/// Non-ASCII values are decoded as UTF-8, falling back to ISO-8859-1, and
+/// values URI encoded by htmx are percent-decoded. The received value is
+/// available through `raw()` on the [strict] variant.
+///
+/// This extractor will always return a value. If the header is not present, or
+/// cannot be decoded, it will return `None`.
 #[derive(Debug, Clone)]
 pub struct HxTriggerName(pub Option<String>);
 
@@ -200,16 +274,14 @@ where
         #[cfg(feature = "auto-vary")]
         parts
             .extensions
-            .get_mut::<crate::auto_vary::HxTriggerNameExtracted>()
+            .get::<crate::auto_vary::HxTriggerNameExtracted>()
             .map(crate::auto_vary::Notifier::notify);
 
-        if let Some(trigger_name) = parts.headers.get(HX_TRIGGER_NAME) {
-            if let Ok(trigger_name) = trigger_name.to_str() {
-                return Ok(HxTriggerName(Some(trigger_name.to_string())));
-            }
-        }
+        let trigger_name = decode_header(&parts.headers, &HX_TRIGGER_NAME)
+            .ok()
+            .flatten();
 
-        Ok(HxTriggerName(None))
+        Ok(HxTriggerName(trigger_name))
     }
 }
 
@@ -219,8 +291,12 @@ where
 /// attribute set. The value will contain the trigger element's id. If the id
 /// does not exist on the page, the value will be None.
 ///
-/// This extractor will always return a value. If the header is not present, it
-/// will return `None`.
+/// Non-ASCII values are decoded as UTF-8, falling back to ISO-8859-1, and
+/// values URI encoded by htmx are percent-decoded. The received value is
+/// available through `raw()` on the [strict] variant.
+///
+/// This extractor will always return a value. If the header is not present, or
+/// cannot be decoded, it will return `None`.
 #[derive(Debug, Clone)]
 pub struct HxTrigger(pub Option<String>);
 
@@ -234,15 +310,11 @@ where
         #[cfg(feature = "auto-vary")]
         parts
             .extensions
-            .get_mut::<crate::auto_vary::HxTriggerExtracted>()
+            .get::<crate::auto_vary::HxTriggerExtracted>()
             .map(crate::auto_vary::Notifier::notify);
 
-        if let Some(trigger) = parts.headers.get(HX_TRIGGER) {
-            if let Ok(trigger) = trigger.to_str() {
-                return Ok(HxTrigger(Some(trigger.to_string())));
-            }
-        }
+        let trigger = decode_header(&parts.headers, &HX_TRIGGER).ok().flatten();
 
-        Ok(HxTrigger(None))
+        Ok(HxTrigger(trigger))
     }
 }
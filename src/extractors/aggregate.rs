@@ -0,0 +1,204 @@
+use axum_core::extract::FromRequestParts;
+use http::{Uri, request::Parts};
+
+use crate::{
+    HX_BOOSTED, HX_CURRENT_URL, HX_HISTORY_RESTORE_REQUEST, HX_PROMPT, HX_REQUEST, HX_TARGET,
+    HX_TRIGGER, HX_TRIGGER_NAME, HxString, encoding::is_uri_encoded,
+};
+
+/// All of the htmx request headers, parsed in a single pass.
+///
+/// This is an alternative to combining several of the individual extractors
+/// (e.g. [`HxRequest`](crate::HxRequest), [`HxTarget`](crate::HxTarget) and
+/// [`HxTrigger`](crate::HxTrigger)) in the same handler. The header map is
+/// walked once and every htmx header is collected along the way.
+///
+/// With the `auto-vary` feature enabled, the corresponding `Vary` header is
+/// only added for the values that are actually read through the accessors
+/// below.
+///
+/// String values are decoded the same way as by the individual extractors, and
+/// are stored as [`HxString`]s to avoid copying them.
+///
+/// This extractor will always return a value. Missing headers are represented
+/// as `false` or `None`.
+#[derive(Debug, Clone, Default)]
+pub struct HxHeaders {
+    request: bool,
+    boosted: bool,
+    history_restore_request: bool,
+    current_url: Option<Uri>,
+    prompt: Option<HxString>,
+    target: Option<HxString>,
+    trigger: Option<HxString>,
+    trigger_name: Option<HxString>,
+    #[cfg(feature = "auto-vary")]
+    notifiers: crate::auto_vary::HxHeadersNotifiers,
+}
+
+impl HxHeaders {
+    /// Returns `true` if the request was made by htmx (`HX-Request`).
+    pub fn is_htmx(&self) -> bool {
+        #[cfg(feature = "auto-vary")]
+        self.notifiers.request();
+
+        self.request
+    }
+
+    /// Returns `true` if the request was made by a boosted element
+    /// (`HX-Boosted`).
+    pub fn is_boosted(&self) -> bool {
+        #[cfg(feature = "auto-vary")]
+        self.notifiers.boosted();
+
+        self.boosted
+    }
+
+    /// Returns `true` if the request is for history restoration after a miss
+    /// in the local history cache (`HX-History-Restore-Request`).
+    pub fn is_history_restore(&self) -> bool {
+        #[cfg(feature = "auto-vary")]
+        self.notifiers.history_restore_request();
+
+        self.history_restore_request
+    }
+
+    /// Classifies the request, see [`HxRequestKind`](crate::HxRequestKind).
+    pub fn kind(&self) -> crate::HxRequestKind {
+        crate::HxRequestKind::from_flags(
+            self.is_htmx(),
+            self.is_boosted(),
+            self.is_history_restore(),
+        )
+    }
+
+    /// The current URL of the browser (`HX-Current-Url`), if it could be
+    /// parsed.
+    pub fn current_url(&self) -> Option<&Uri> {
+        self.current_url.as_ref()
+    }
+
+    /// The user response to an `hx-prompt` (`HX-Prompt`).
+    pub fn prompt(&self) -> Option<&str> {
+        self.prompt.as_deref()
+    }
+
+    /// The `id` of the target element (`HX-Target`).
+    pub fn target(&self) -> Option<&str> {
+        #[cfg(feature = "auto-vary")]
+        self.notifiers.target();
+
+        self.target.as_deref()
+    }
+
+    /// The `id` of the triggered element (`HX-Trigger`).
+    pub fn trigger(&self) -> Option<&str> {
+        #[cfg(feature = "auto-vary")]
+        self.notifiers.trigger();
+
+        self.trigger.as_deref()
+    }
+
+    /// The `name` of the triggered element (`HX-Trigger-Name`).
+    pub fn trigger_name(&self) -> Option<&str> {
+        #[cfg(feature = "auto-vary")]
+        self.notifiers.trigger_name();
+
+        self.trigger_name.as_deref()
+    }
+}
+
+impl<S> FromRequestParts<S> for HxHeaders
+where
+    S: Send + Sync,
+{
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<Self, Self::Rejection> {
+        let mut headers = HxHeaders {
+            #[cfg(feature = "auto-vary")]
+            notifiers: crate::auto_vary::HxHeadersNotifiers::from_extensions(&parts.extensions),
+            ..Default::default()
+        };
+
+        let string = |name, value| {
+            HxString::from_header_value(value, is_uri_encoded(&parts.headers, name)).ok()
+        };
+
+        for (name, value) in &parts.headers {
+            if name == HX_REQUEST {
+                headers.request = true;
+            } else if name == HX_BOOSTED {
+                headers.boosted = true;
+            } else if name == HX_HISTORY_RESTORE_REQUEST {
+                headers.history_restore_request = true;
+            } else if name == HX_CURRENT_URL && headers.current_url.is_none() {
+                headers.current_url = value.to_str().ok().and_then(|url| url.parse().ok());
+            } else if name == HX_PROMPT && headers.prompt.is_none() {
+                headers.prompt = string(name, value);
+            } else if name == HX_TARGET && headers.target.is_none() {
+                headers.target = string(name, value);
+            } else if name == HX_TRIGGER && headers.trigger.is_none() {
+                headers.trigger = string(name, value);
+            } else if name == HX_TRIGGER_NAME && headers.trigger_name.is_none() {
+                headers.trigger_name = string(name, value);
+            }
+        }
+
+        Ok(headers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::Request;
+
+    use super::*;
+
+    async fn extract(request: Request<()>) -> HxHeaders {
+        let (mut parts, _) = request.into_parts();
+        HxHeaders::from_request_parts(&mut parts, &())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn parses_all_headers() {
+        let headers = extract(
+            Request::builder()
+                .header(HX_REQUEST, "true")
+                .header(HX_BOOSTED, "true")
+                .header(HX_CURRENT_URL, "https://example.com/foo?bar=baz")
+                .header(HX_PROMPT, "yes")
+                .header(HX_TARGET, "main")
+                .header(HX_TRIGGER, "button")
+                .header(HX_TRIGGER_NAME, "submit")
+                .body(())
+                .unwrap(),
+        )
+        .await;
+
+        assert!(headers.is_htmx());
+        assert!(headers.is_boosted());
+        assert!(!headers.is_history_restore());
+        assert_eq!(headers.current_url().unwrap().path(), "/foo");
+        assert_eq!(headers.prompt(), Some("yes"));
+        assert_eq!(headers.target(), Some("main"));
+        assert_eq!(headers.trigger(), Some("button"));
+        assert_eq!(headers.trigger_name(), Some("submit"));
+    }
+
+    #[tokio::test]
+    async fn missing_headers() {
+        let headers = extract(Request::new(())).await;
+
+        assert!(!headers.is_htmx());
+        assert!(!headers.is_boosted());
+        assert!(!headers.is_history_restore());
+        assert!(headers.current_url().is_none());
+        assert!(headers.prompt().is_none());
+        assert!(headers.target().is_none());
+        assert!(headers.trigger().is_none());
+        assert!(headers.trigger_name().is_none());
+    }
+}
@@ -0,0 +1,308 @@
+use http::{Uri, request::Parts, uri::Authority};
+#[cfg(feature = "serde")]
+use {
+    crate::{HX_CURRENT_URL, HxHeaderRejection},
+    axum_core::extract::{FromRequestParts, OptionalFromRequestParts},
+    serde::de::DeserializeOwned,
+};
+
+/// Returns the origin (`scheme://host[:port]`) of an absolute `uri`.
+pub(crate) fn origin(uri: &Uri) -> Option<String> {
+    Some(format!("{}://{}", uri.scheme()?, uri.authority()?))
+}
+
+/// Returns `true` if the authority of `uri` matches the host the request was
+/// sent to, taken from the `Host` header or, for HTTP/2, the request URI.
+pub(crate) fn is_same_origin(uri: &Uri, parts: &Parts) -> bool {
+    let Some(authority) = uri.authority() else {
+        return false;
+    };
+
+    let host = parts
+        .headers
+        .get(http::header::HOST)
+        .and_then(|host| host.to_str().ok())
+        .and_then(|host| host.parse::<Authority>().ok())
+        .or_else(|| parts.uri.authority().cloned());
+
+    host.is_some_and(|host| {
+        host.host().eq_ignore_ascii_case(authority.host())
+            && host.port_u16().or(default_port(uri)) == authority.port_u16().or(default_port(uri))
+    })
+}
+
+fn default_port(uri: &Uri) -> Option<u16> {
+    match uri.scheme_str() {
+        Some("http") => Some(80),
+        Some("https") => Some(443),
+        _ => None,
+    }
+}
+
+/// Matches `path` against an axum-style route template such as
+/// `/projects/{id}/tasks`, returning the percent-decoded captures.
+///
+/// Supports `{name}` captures of a single segment and a trailing `{*name}`
+/// wildcard capturing the rest of the path.
+pub(crate) fn match_route<'a>(route: &'a str, path: &str) -> Option<Vec<(&'a str, String)>> {
+    let mut params = Vec::new();
+    let mut segments = path.split('/');
+
+    for part in route.split('/') {
+        if let Some(name) = part.strip_prefix("{*").and_then(|p| p.strip_suffix('}')) {
+            let rest = segments.by_ref().collect::<Vec<_>>().join("/");
+            if rest.is_empty() {
+                return None;
+            }
+            params.push((name, decode_segment(&rest)?));
+            return Some(params);
+        }
+
+        let segment = segments.next()?;
+        match part.strip_prefix('{').and_then(|p| p.strip_suffix('}')) {
+            Some(name) if !segment.is_empty() => params.push((name, decode_segment(segment)?)),
+            Some(_) => return None,
+            None if part == segment => {}
+            None => return None,
+        }
+    }
+
+    segments.next().is_none().then_some(params)
+}
+
+fn decode_segment(segment: &str) -> Option<String> {
+    String::from_utf8(crate::encoding::percent_decode(segment.as_bytes()).ok()?).ok()
+}
+
+/// Deserializes the captures of [`match_route`] into `T`.
+#[cfg(feature = "serde")]
+pub(crate) fn path_params<T: DeserializeOwned>(
+    uri: &Uri,
+    route: &str,
+) -> Result<Option<T>, HxHeaderRejection> {
+    let Some(params) = match_route(route, uri.path()) else {
+        return Ok(None);
+    };
+
+    // Round-trip through the urlencoded format, so that the captured strings
+    // are parsed into numbers, booleans, etc. as needed by `T`.
+    serde_urlencoded::to_string(&params)
+        .map_err(|err| err.to_string())
+        .and_then(|query| serde_urlencoded::from_str(&query).map_err(|err| err.to_string()))
+        .map(Some)
+        .map_err(|message| HxHeaderRejection::FailedToDeserialize {
+            name: HX_CURRENT_URL,
+            message,
+        })
+}
+
+/// Deserializes the query string of the `HX-Current-Url` header.
+///
+/// This is the equivalent of axum's `Query` extractor for the page the browser
+/// is currently on, instead of the request itself. It is useful when a partial
+/// refresh needs to respect the filters or sort order of the page it was
+/// triggered from.
+///
+/// Rejects the request if the header is missing, is not a valid URI, or its
+/// query string cannot be deserialized into `T`. A URL without a query string
+/// is deserialized from an empty one.
+///
+/// ```rust
+/// use axum_htmx::HxCurrentQuery;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Filters {
+///     status: Option<String>,
+///     sort: Option<String>,
+/// }
+///
+/// async fn refresh_list(HxCurrentQuery(filters): HxCurrentQuery<Filters>) {
+///     // ...
+/// }
+/// ```
+#[cfg(feature = "serde")]
+#[cfg_attr(feature = "unstable", doc(cfg(feature = "serde")))]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HxCurrentQuery<T>(pub T);
+
+#[cfg(feature = "serde")]
+#[cfg_attr(feature = "unstable", doc(cfg(feature = "serde")))]
+impl<T, S> OptionalFromRequestParts<S> for HxCurrentQuery<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = HxHeaderRejection;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &S,
+    ) -> Result<Option<Self>, Self::Rejection> {
+        let Some(url) =
+            <crate::strict::HxCurrentUrl as OptionalFromRequestParts<S>>::from_request_parts(
+                parts, state,
+            )
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        serde_urlencoded::from_str(url.query().unwrap_or_default())
+            .map(|query| Some(HxCurrentQuery(query)))
+            .map_err(|err| HxHeaderRejection::FailedToDeserialize {
+                name: HX_CURRENT_URL,
+                message: err.to_string(),
+            })
+    }
+}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(feature = "unstable", doc(cfg(feature = "serde")))]
+impl<T, S> FromRequestParts<S> for HxCurrentQuery<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = HxHeaderRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        <Self as OptionalFromRequestParts<S>>::from_request_parts(parts, state)
+            .await?
+            .ok_or(HxHeaderRejection::Missing(HX_CURRENT_URL))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::Request;
+
+    use super::*;
+
+    fn parts(host: &str) -> Parts {
+        Request::builder()
+            .header(http::header::HOST, host)
+            .body(())
+            .unwrap()
+            .into_parts()
+            .0
+    }
+
+    #[test]
+    fn origin_of_absolute_uri() {
+        let uri: Uri = "https://example.com:8443/foo?bar=baz".parse().unwrap();
+        assert_eq!(origin(&uri).unwrap(), "https://example.com:8443");
+
+        let uri: Uri = "/foo".parse().unwrap();
+        assert!(origin(&uri).is_none());
+    }
+
+    #[test]
+    fn same_origin() {
+        let uri: Uri = "https://example.com/foo".parse().unwrap();
+        assert!(is_same_origin(&uri, &parts("example.com")));
+        assert!(is_same_origin(&uri, &parts("EXAMPLE.com:443")));
+        assert!(!is_same_origin(&uri, &parts("example.com:8443")));
+        assert!(!is_same_origin(&uri, &parts("evil.com")));
+
+        let uri: Uri = "/foo".parse().unwrap();
+        assert!(!is_same_origin(&uri, &parts("example.com")));
+    }
+
+    #[test]
+    fn route_matching() {
+        assert_eq!(match_route("/projects", "/projects"), Some(vec![]));
+        assert_eq!(
+            match_route("/projects/{id}/tasks", "/projects/42/tasks"),
+            Some(vec![("id", "42".to_string())])
+        );
+        assert_eq!(
+            match_route("/users/{name}", "/users/caf%C3%A9"),
+            Some(vec![("name", "café".to_string())])
+        );
+        assert_eq!(
+            match_route("/files/{*path}", "/files/a/b/c.txt"),
+            Some(vec![("path", "a/b/c.txt".to_string())])
+        );
+
+        assert_eq!(match_route("/projects/{id}/tasks", "/projects/42"), None);
+        assert_eq!(match_route("/projects/{id}", "/projects/42/tasks"), None);
+        assert_eq!(match_route("/projects/{id}", "/projects/"), None);
+        assert_eq!(match_route("/projects/{id}", "/tasks/42"), None);
+        assert_eq!(match_route("/files/{*path}", "/files"), None);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn typed_path_params() {
+        use serde::Deserialize;
+
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Params {
+            project: u32,
+            task: String,
+        }
+
+        let uri: Uri = "https://example.com/projects/42/tasks/setup"
+            .parse()
+            .unwrap();
+        let route = "/projects/{project}/tasks/{task}";
+
+        assert_eq!(
+            path_params::<Params>(&uri, route).unwrap(),
+            Some(Params {
+                project: 42,
+                task: "setup".to_string()
+            })
+        );
+        assert!(
+            path_params::<Params>(&uri, "/projects/{project}")
+                .unwrap()
+                .is_none()
+        );
+
+        let uri: Uri = "https://example.com/projects/abc/tasks/setup"
+            .parse()
+            .unwrap();
+        assert!(path_params::<Params>(&uri, route).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[tokio::test]
+    async fn current_query() {
+        use axum::{Router, routing::get};
+        use serde::Deserialize;
+
+        #[derive(Deserialize)]
+        struct Filters {
+            status: String,
+            page: Option<u32>,
+        }
+
+        let app = Router::new().route(
+            "/",
+            get(
+                |HxCurrentQuery(filters): HxCurrentQuery<Filters>| async move {
+                    format!("{} {:?}", filters.status, filters.page)
+                },
+            ),
+        );
+        let server = axum_test::TestServer::new(app).unwrap();
+
+        let resp = server
+            .get("/")
+            .add_header(
+                HX_CURRENT_URL,
+                "https://example.com/list?status=open&page=2",
+            )
+            .await;
+        resp.assert_text("open Some(2)");
+
+        let resp = server
+            .get("/")
+            .add_header(HX_CURRENT_URL, "https://example.com/list?page=2")
+            .expect_failure()
+            .await;
+        resp.assert_status_bad_request();
+    }
+}
@@ -0,0 +1,136 @@
+use axum_core::extract::FromRequestParts;
+use http::request::Parts;
+
+use crate::{HX_BOOSTED, HX_HISTORY_RESTORE_REQUEST, HX_REQUEST};
+
+/// Classifies a request by how its response is going to be rendered.
+///
+/// This combines the `HX-Request`, `HX-Boosted` and
+/// `HX-History-Restore-Request` headers, in that order of precedence:
+///
+/// 1. A history restore request wants the full page, as htmx will replace the
+///    whole body with the response after a miss in its history cache.
+/// 2. A request without `HX-Request` was not made by htmx, so it is a regular
+///    full page load.
+/// 3. A request from a boosted element is a navigation, where htmx swaps the
+///    body and picks up the `<title>`.
+/// 4. Any other htmx request is a partial update.
+///
+/// With the `auto-vary` feature enabled, all three headers are added to the
+/// `Vary` header.
+///
+/// This extractor will always return a value.
+///
+/// ```rust
+/// use axum_htmx::HxRequestKind;
+///
+/// async fn index(kind: HxRequestKind) -> &'static str {
+///     if kind.needs_layout() {
+///         "<html><body><main>Hello, world!</main></body></html>"
+///     } else {
+///         "<main>Hello, world!</main>"
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HxRequestKind {
+    /// A regular request, not made by htmx.
+    FullPage,
+    /// An htmx request swapping part of the page.
+    Partial,
+    /// An htmx request made by a boosted element.
+    Boosted,
+    /// An htmx request restoring the page after a history cache miss.
+    HistoryRestore,
+}
+
+impl HxRequestKind {
+    pub(crate) fn from_flags(request: bool, boosted: bool, history_restore: bool) -> Self {
+        if history_restore {
+            HxRequestKind::HistoryRestore
+        } else if !request {
+            HxRequestKind::FullPage
+        } else if boosted {
+            HxRequestKind::Boosted
+        } else {
+            HxRequestKind::Partial
+        }
+    }
+
+    /// Returns `true` if the response should be wrapped in the full page
+    /// layout.
+    ///
+    /// This is the case for [`FullPage`](Self::FullPage) and
+    /// [`HistoryRestore`](Self::HistoryRestore) requests.
+    pub fn needs_layout(&self) -> bool {
+        matches!(
+            self,
+            HxRequestKind::FullPage | HxRequestKind::HistoryRestore
+        )
+    }
+
+    /// Returns `true` if the request was made by htmx.
+    pub fn is_htmx(&self) -> bool {
+        !matches!(self, HxRequestKind::FullPage)
+    }
+}
+
+impl<S> FromRequestParts<S> for HxRequestKind
+where
+    S: Send + Sync,
+{
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<Self, Self::Rejection> {
+        #[cfg(feature = "auto-vary")]
+        {
+            use crate::auto_vary::{
+                HxBoostedExtracted, HxHistoryRestoreRequestExtracted, HxRequestExtracted, Notifier,
+            };
+
+            parts
+                .extensions
+                .get::<HxRequestExtracted>()
+                .map(Notifier::notify);
+            parts
+                .extensions
+                .get::<HxBoostedExtracted>()
+                .map(Notifier::notify);
+            parts
+                .extensions
+                .get::<HxHistoryRestoreRequestExtracted>()
+                .map(Notifier::notify);
+        }
+
+        Ok(HxRequestKind::from_flags(
+            parts.headers.contains_key(HX_REQUEST),
+            parts.headers.contains_key(HX_BOOSTED),
+            parts.headers.contains_key(HX_HISTORY_RESTORE_REQUEST),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn precedence() {
+        use HxRequestKind::*;
+
+        assert_eq!(HxRequestKind::from_flags(false, false, false), FullPage);
+        assert_eq!(HxRequestKind::from_flags(false, true, false), FullPage);
+        assert_eq!(HxRequestKind::from_flags(true, false, false), Partial);
+        assert_eq!(HxRequestKind::from_flags(true, true, false), Boosted);
+        assert_eq!(HxRequestKind::from_flags(true, true, true), HistoryRestore);
+        assert_eq!(HxRequestKind::from_flags(true, false, true), HistoryRestore);
+    }
+
+    #[test]
+    fn needs_layout() {
+        assert!(HxRequestKind::FullPage.needs_layout());
+        assert!(HxRequestKind::HistoryRestore.needs_layout());
+        assert!(!HxRequestKind::Partial.needs_layout());
+        assert!(!HxRequestKind::Boosted.needs_layout());
+    }
+}
@@ -0,0 +1,113 @@
+use std::{fmt, str::FromStr};
+
+use axum_core::extract::{FromRequestParts, OptionalFromRequestParts};
+use http::request::Parts;
+
+use crate::{HX_PROMPT, HxHeaderRejection, strict};
+
+/// The `HX-Prompt` header, parsed into `T`.
+///
+/// The user response to an `hx-prompt` is parsed with [`FromStr`], after
+/// trimming surrounding whitespace, so that handlers don't have to validate
+/// numbers, dates or confirmation phrases themselves.
+///
+/// Rejects the request with `400 Bad Request` if the header is missing or
+/// cannot be decoded, and with `422 Unprocessable Entity` if the value cannot
+/// be parsed. The body of the latter is plain text with the parsing error, and
+/// leaves out the value typed by the user. The error message of `T` is not
+/// HTML-escaped though, so escape the body before inserting it into the page
+/// (htmx does not swap error responses by default, see
+/// [`htmx.config.responseHandling`](https://htmx.org/docs/#response-handling)).
+///
+/// ```rust
+/// use axum_htmx::HxPromptParsed;
+///
+/// async fn set_quantity(HxPromptParsed(quantity): HxPromptParsed<u32>) -> String {
+///     format!("quantity set to {quantity}")
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HxPromptParsed<T>(pub T);
+
+impl<T, S> OptionalFromRequestParts<S> for HxPromptParsed<T>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+    S: Send + Sync,
+{
+    type Rejection = HxHeaderRejection;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &S,
+    ) -> Result<Option<Self>, Self::Rejection> {
+        let Some(prompt) =
+            <strict::HxPrompt as OptionalFromRequestParts<S>>::from_request_parts(parts, state)
+                .await?
+        else {
+            return Ok(None);
+        };
+
+        prompt
+            .trim()
+            .parse()
+            .map(|value| Some(HxPromptParsed(value)))
+            .map_err(|err: T::Err| HxHeaderRejection::FailedToParse {
+                name: HX_PROMPT,
+                message: format!("Invalid value: {err}"),
+            })
+    }
+}
+
+impl<T, S> FromRequestParts<S> for HxPromptParsed<T>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+    S: Send + Sync,
+{
+    type Rejection = HxHeaderRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        <Self as OptionalFromRequestParts<S>>::from_request_parts(parts, state)
+            .await?
+            .ok_or(HxHeaderRejection::Missing(HX_PROMPT))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{Router, routing::get};
+    use http::StatusCode;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn parsed_prompt() {
+        let app = Router::new().route(
+            "/",
+            get(|HxPromptParsed(n): HxPromptParsed<u32>| async move { (n * 2).to_string() }),
+        );
+        let server = axum_test::TestServer::new(app).unwrap();
+
+        let resp = server.get("/").add_header(HX_PROMPT, " 21 ").await;
+        resp.assert_text("42");
+
+        let resp = server
+            .get("/")
+            .add_header(HX_PROMPT, "many")
+            .expect_failure()
+            .await;
+        resp.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+        resp.assert_text("Invalid value: invalid digit found in string");
+
+        let resp = server
+            .get("/")
+            .add_header(HX_PROMPT, "<img src=x onerror=alert(1)>")
+            .expect_failure()
+            .await;
+        resp.assert_text("Invalid value: invalid digit found in string");
+
+        let resp = server.get("/").expect_failure().await;
+        resp.assert_status_bad_request();
+    }
+}
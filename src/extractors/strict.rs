@@ -0,0 +1,390 @@
+//! Strict variants of the htmx request header extractors.
+//!
+//! Unlike the extractors in the crate root, which always succeed, these reject
+//! the request with a [`HxHeaderRejection`] when the header is missing or its
+//! value cannot be decoded. Wrap them in an `Option` to only reject invalid
+//! values.
+//!
+//! Only these extractors implement `OptionalFromRequestParts`, so an `Option`
+//! can only wrap the strict variant. The extractors in the crate root already
+//! hold an `Option` of the value, e.g. `axum_htmx::HxTarget(Option<String>)`,
+//! and cannot be wrapped in an `Option` themselves.
+//!
+//! The string values are stored as an [`HxString`], which borrows the received
+//! header value instead of copying it whenever possible:
+//!
+//! ```rust
+//! use axum_htmx::strict::{HxPrompt, HxTarget};
+//!
+//! // Responds with `400 Bad Request` unless `HX-Target` is present.
+//! async fn requires_target(target: HxTarget) -> String {
+//!     format!("swapping into #{}", target.as_str())
+//! }
+//!
+//! // `None` if `HX-Prompt` is missing, `400 Bad Request` if it is malformed.
+//! async fn optional_prompt(prompt: Option<HxPrompt>) -> String {
+//!     prompt.map(HxPrompt::into_inner).unwrap_or_default()
+//! }
+//! ```
+
+use std::ops::Deref;
+
+use axum_core::extract::{FromRequestParts, OptionalFromRequestParts};
+use http::{HeaderName, HeaderValue, Uri, request::Parts};
+
+use super::current_url;
+use crate::{
+    HX_CURRENT_URL, HX_PROMPT, HX_TARGET, HX_TRIGGER, HX_TRIGGER_NAME, HxHeaderRejection, HxString,
+};
+
+fn decode(parts: &Parts, name: HeaderName) -> Result<Option<HxString>, HxHeaderRejection> {
+    HxString::from_headers(&parts.headers, &name).map_err(|()| HxHeaderRejection::Invalid(name))
+}
+
+macro_rules! impl_str_extractor {
+    ($name:ident, $header:ident $(, $notifier:ident)?) => {
+        impl $name {
+            /// Returns the decoded header value as a string slice.
+            pub fn as_str(&self) -> &str {
+                self.0.as_str()
+            }
+
+            /// Returns the header value as it was received.
+            pub fn raw(&self) -> &HeaderValue {
+                self.0.raw()
+            }
+
+            /// Consumes the extractor, returning the decoded header value.
+            ///
+            /// This copies the value, see [`into_hx_string`](Self::into_hx_string)
+            /// for a cheap alternative.
+            pub fn into_inner(self) -> String {
+                self.0.into()
+            }
+
+            /// Consumes the extractor, returning the decoded header value
+            /// without copying it.
+            pub fn into_hx_string(self) -> HxString {
+                self.0
+            }
+        }
+
+        impl Deref for $name {
+            type Target = str;
+
+            fn deref(&self) -> &Self::Target {
+                self.0.as_str()
+            }
+        }
+
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                self.0.as_str()
+            }
+        }
+
+        impl<S> OptionalFromRequestParts<S> for $name
+        where
+            S: Send + Sync,
+        {
+            type Rejection = HxHeaderRejection;
+
+            async fn from_request_parts(
+                parts: &mut Parts,
+                _: &S,
+            ) -> Result<Option<Self>, Self::Rejection> {
+                $(
+                    #[cfg(feature = "auto-vary")]
+                    if let Some(notifier) = parts.extensions.get::<crate::auto_vary::$notifier>() {
+                        crate::auto_vary::Notifier::notify(notifier);
+                    }
+                )?
+
+                Ok(decode(parts, $header)?.map($name))
+            }
+        }
+
+        impl<S> FromRequestParts<S> for $name
+        where
+            S: Send + Sync,
+        {
+            type Rejection = HxHeaderRejection;
+
+            async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+                <Self as OptionalFromRequestParts<S>>::from_request_parts(parts, state)
+                    .await?
+                    .ok_or(HxHeaderRejection::Missing($header))
+            }
+        }
+    };
+}
+
+/// The `HX-Current-Url` header.
+///
+/// Rejects the request if the header is missing or is not a valid URI.
+///
+/// Besides the URI itself, this reports whether the current URL is on the same
+/// origin as the request, see [`is_same_origin`](Self::is_same_origin). The
+/// header is set by the client, so it should not be trusted otherwise.
+///
+/// See [`HxCurrentUrl`](crate::HxCurrentUrl) for the infallible variant.
+#[derive(Debug, Clone)]
+pub struct HxCurrentUrl {
+    uri: Uri,
+    same_origin: bool,
+}
+
+impl HxCurrentUrl {
+    /// Returns the parsed URI.
+    pub fn uri(&self) -> &Uri {
+        &self.uri
+    }
+
+    /// Returns the path of the current URL.
+    pub fn path(&self) -> &str {
+        self.uri.path()
+    }
+
+    /// Returns the query string of the current URL, if any.
+    pub fn query(&self) -> Option<&str> {
+        self.uri.query()
+    }
+
+    /// Returns the origin (`scheme://host[:port]`) of the current URL, if it
+    /// is absolute.
+    pub fn origin(&self) -> Option<String> {
+        current_url::origin(&self.uri)
+    }
+
+    /// Returns `true` if the current URL points to the host the request was
+    /// sent to, according to the `Host` header.
+    ///
+    /// The scheme is not compared, as it is usually not known behind a proxy.
+    pub fn is_same_origin(&self) -> bool {
+        self.same_origin
+    }
+
+    /// Returns `true` if the path of the current URL matches an axum-style
+    /// route template, such as `/projects/{id}/tasks`.
+    pub fn matches_route(&self, route: &str) -> bool {
+        current_url::match_route(route, self.uri.path()).is_some()
+    }
+
+    /// Matches the path of the current URL against an axum-style route
+    /// template, such as `/projects/{id}/tasks`, and deserializes the captured
+    /// segments into `T`.
+    ///
+    /// This works like axum's `Path` extractor, but for the page the browser
+    /// is currently on. `T` has to deserialize from a map, e.g. a struct with
+    /// a field for each capture.
+    ///
+    /// Returns `Ok(None)` if the path does not match the route, and an error
+    /// if the captures cannot be deserialized into `T`.
+    ///
+    /// ```rust
+    /// use axum_htmx::{HxHeaderRejection, strict::HxCurrentUrl};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct ProjectParams {
+    ///     id: u32,
+    /// }
+    ///
+    /// async fn refresh(url: HxCurrentUrl) -> Result<String, HxHeaderRejection> {
+    ///     match url.path_params::<ProjectParams>("/projects/{id}/tasks")? {
+    ///         Some(params) => Ok(format!("refreshing tasks of project {}", params.id)),
+    ///         None => Ok("not on a project page".to_string()),
+    ///     }
+    /// }
+    /// ```
+    #[cfg(feature = "serde")]
+    #[cfg_attr(feature = "unstable", doc(cfg(feature = "serde")))]
+    pub fn path_params<T: serde::de::DeserializeOwned>(
+        &self,
+        route: &str,
+    ) -> Result<Option<T>, HxHeaderRejection> {
+        current_url::path_params(&self.uri, route)
+    }
+
+    /// Consumes the extractor, returning the parsed URI.
+    pub fn into_inner(self) -> Uri {
+        self.uri
+    }
+}
+
+impl<S> OptionalFromRequestParts<S> for HxCurrentUrl
+where
+    S: Send + Sync,
+{
+    type Rejection = HxHeaderRejection;
+
+    async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<Option<Self>, Self::Rejection> {
+        let Some(url) = decode(parts, HX_CURRENT_URL)? else {
+            return Ok(None);
+        };
+
+        let uri: Uri = url
+            .as_str()
+            .parse()
+            .map_err(|_| HxHeaderRejection::Invalid(HX_CURRENT_URL))?;
+
+        Ok(Some(HxCurrentUrl {
+            same_origin: current_url::is_same_origin(&uri, parts),
+            uri,
+        }))
+    }
+}
+
+impl<S> FromRequestParts<S> for HxCurrentUrl
+where
+    S: Send + Sync,
+{
+    type Rejection = HxHeaderRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        <Self as OptionalFromRequestParts<S>>::from_request_parts(parts, state)
+            .await?
+            .ok_or(HxHeaderRejection::Missing(HX_CURRENT_URL))
+    }
+}
+
+/// The `HX-Prompt` header.
+///
+/// Rejects the request if the header is missing or cannot be decoded.
+///
+/// See [`HxPrompt`](crate::HxPrompt) for the infallible variant.
+#[derive(Debug, Clone)]
+pub struct HxPrompt(HxString);
+
+impl_str_extractor!(HxPrompt, HX_PROMPT);
+
+/// The `HX-Target` header.
+///
+/// Rejects the request if the header is missing or cannot be decoded.
+///
+/// See [`HxTarget`](crate::HxTarget) for the infallible variant.
+#[derive(Debug, Clone)]
+pub struct HxTarget(HxString);
+
+impl_str_extractor!(HxTarget, HX_TARGET, HxTargetExtracted);
+
+/// The `HX-Trigger-Name` header.
+///
+/// Rejects the request if the header is missing or cannot be decoded.
+///
+/// See [`HxTriggerName`](crate::HxTriggerName) for the infallible variant.
+#[derive(Debug, Clone)]
+pub struct HxTriggerName(HxString);
+
+impl_str_extractor!(HxTriggerName, HX_TRIGGER_NAME, HxTriggerNameExtracted);
+
+/// The `HX-Trigger` header.
+///
+/// Rejects the request if the header is missing or cannot be decoded.
+///
+/// See [`HxTrigger`](crate::HxTrigger) for the infallible variant.
+#[derive(Debug, Clone)]
+pub struct HxTrigger(HxString);
+
+impl_str_extractor!(HxTrigger, HX_TRIGGER, HxTriggerExtracted);
+
+#[cfg(test)]
+mod tests {
+    use axum::{Router, routing::get};
+    use http::StatusCode;
+
+    use super::*;
+
+    fn server() -> axum_test::TestServer {
+        let app = Router::new()
+            .route(
+                "/required",
+                get(|target: HxTarget| async move { target.into_inner() }),
+            )
+            .route(
+                "/optional",
+                get(|target: Option<HxTarget>| async move {
+                    target.map(HxTarget::into_inner).unwrap_or_default()
+                }),
+            )
+            .route(
+                "/current-url",
+                get(|url: HxCurrentUrl| async move {
+                    format!("{} {}", url.path(), url.is_same_origin())
+                }),
+            );
+        axum_test::TestServer::new(app).unwrap()
+    }
+
+    #[tokio::test]
+    async fn required_header() {
+        let server = server();
+
+        let resp = server.get("/required").add_header(HX_TARGET, "main").await;
+        resp.assert_status_ok();
+        resp.assert_text("main");
+
+        let resp = server.get("/required").expect_failure().await;
+        resp.assert_status(StatusCode::BAD_REQUEST);
+        resp.assert_text("Missing request header `hx-target`");
+    }
+
+    #[tokio::test]
+    async fn optional_header() {
+        let server = server();
+
+        let resp = server.get("/optional").await;
+        resp.assert_status_ok();
+        resp.assert_text("");
+
+        let resp = server
+            .get("/optional")
+            .add_header(HX_TARGET, "%E2%9C")
+            .add_header("hx-target-uri-autoencoded", "true")
+            .expect_failure()
+            .await;
+        resp.assert_status(StatusCode::BAD_REQUEST);
+        resp.assert_text("Invalid request header `hx-target`");
+    }
+
+    #[tokio::test]
+    async fn decoded_header() {
+        let resp = server()
+            .get("/required")
+            .add_header(HX_TARGET, HeaderValue::from_bytes(b"caf\xe9").unwrap())
+            .await;
+        resp.assert_text("café");
+
+        let resp = server()
+            .get("/required")
+            .add_header(HX_TARGET, "%E2%9C%93")
+            .add_header("hx-target-uri-autoencoded", "true")
+            .await;
+        resp.assert_text("✓");
+    }
+
+    #[tokio::test]
+    async fn invalid_current_url() {
+        let server = server();
+
+        let resp = server
+            .get("/current-url")
+            .add_header(HX_CURRENT_URL, "https://example.com/foo")
+            .await;
+        resp.assert_text("/foo false");
+
+        let resp = server
+            .get("/current-url")
+            .add_header(HX_CURRENT_URL, "https://example.com/foo")
+            .add_header(http::header::HOST, "example.com")
+            .await;
+        resp.assert_text("/foo true");
+
+        let resp = server
+            .get("/current-url")
+            .add_header(HX_CURRENT_URL, "not a url")
+            .expect_failure()
+            .await;
+        resp.assert_status(StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,212 @@
+use std::{borrow::Borrow, fmt, ops::Deref, sync::Arc};
+
+use http::{HeaderMap, HeaderName, HeaderValue};
+
+use crate::encoding::{decode_header_value, is_uri_encoded, percent_encode};
+
+/// A decoded htmx request header value that is cheap to clone.
+///
+/// Values that are already valid UTF-8 borrow the received [`HeaderValue`], so
+/// reading them through [`as_str`](Self::as_str) does not allocate. Only values
+/// that need decoding (ISO-8859-1 or URI encoded) are copied once, into a
+/// shared buffer.
+///
+/// Used by the [strict](crate::strict) extractors and by
+/// [`HxHeaders`](crate::HxHeaders).
+#[derive(Clone)]
+pub struct HxString {
+    raw: HeaderValue,
+    decoded: Option<Arc<str>>,
+}
+
+impl HxString {
+    pub(crate) fn from_headers(headers: &HeaderMap, name: &HeaderName) -> Result<Option<Self>, ()> {
+        match headers.get(name) {
+            Some(raw) => Self::from_header_value(raw, is_uri_encoded(headers, name)).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    pub(crate) fn from_header_value(raw: &HeaderValue, uri_encoded: bool) -> Result<Self, ()> {
+        let decoded = if !uri_encoded && std::str::from_utf8(raw.as_bytes()).is_ok() {
+            None
+        } else {
+            Some(decode_header_value(raw, uri_encoded)?.into())
+        };
+
+        Ok(Self {
+            raw: raw.clone(),
+            decoded,
+        })
+    }
+
+    /// Returns the decoded value as a string slice.
+    pub fn as_str(&self) -> &str {
+        match &self.decoded {
+            Some(decoded) => decoded,
+            // Only constructed without a decoded value if `raw` is valid UTF-8.
+            None => std::str::from_utf8(self.raw.as_bytes()).unwrap_or_default(),
+        }
+    }
+
+    /// Returns the header value as it was received.
+    pub fn raw(&self) -> &HeaderValue {
+        &self.raw
+    }
+}
+
+impl Deref for HxString {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for HxString {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Borrow<str> for HxString {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl PartialEq for HxString {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for HxString {}
+
+impl PartialEq<str> for HxString {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for HxString {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl std::hash::Hash for HxString {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state);
+    }
+}
+
+impl fmt::Debug for HxString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for HxString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+/// Decodes a header value that is not URI encoded, as UTF-8 or ISO-8859-1.
+impl From<HeaderValue> for HxString {
+    fn from(value: HeaderValue) -> Self {
+        let decoded = match std::str::from_utf8(value.as_bytes()) {
+            Ok(_) => None,
+            Err(_) => Some(
+                value
+                    .as_bytes()
+                    .iter()
+                    .map(|&b| char::from(b))
+                    .collect::<String>()
+                    .into(),
+            ),
+        };
+
+        Self {
+            raw: value,
+            decoded,
+        }
+    }
+}
+
+/// Creates a value from a string, e.g. to insert a typed header or in tests.
+///
+/// Strings that cannot be sent as-is in a header are percent-encoded in
+/// [`raw`](HxString::raw), as htmx does for URI encoded headers.
+impl From<&str> for HxString {
+    fn from(value: &str) -> Self {
+        Self::from(value.to_owned())
+    }
+}
+
+/// Creates a value from a string, percent-encoding it in
+/// [`raw`](HxString::raw) if needed.
+impl From<String> for HxString {
+    fn from(value: String) -> Self {
+        if let Ok(raw) = HeaderValue::from_str(&value) {
+            return Self { raw, decoded: None };
+        }
+
+        let raw = HeaderValue::try_from(percent_encode(&value).into_owned())
+            .expect("percent-encoded values are visible ASCII");
+        Self {
+            raw,
+            decoded: Some(value.into()),
+        }
+    }
+}
+
+impl From<HxString> for String {
+    fn from(value: HxString) -> Self {
+        value.as_str().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn borrows_utf8_values() {
+        let raw = HeaderValue::from_static("main");
+        let value = HxString::from_header_value(&raw, false).unwrap();
+
+        assert!(value.decoded.is_none());
+        assert_eq!(value, "main");
+        assert_eq!(value.raw(), &raw);
+    }
+
+    #[test]
+    fn decodes_other_values() {
+        let raw = HeaderValue::from_bytes(b"caf\xe9").unwrap();
+        let value = HxString::from_header_value(&raw, false).unwrap();
+        assert_eq!(value, "café");
+        assert_eq!(value.raw().as_bytes(), b"caf\xe9");
+
+        let raw = HeaderValue::from_static("%E2%9C%93");
+        let value = HxString::from_header_value(&raw, true).unwrap();
+        assert_eq!(value, "✓");
+    }
+
+    #[test]
+    fn from_strings() {
+        let value = HxString::from("main");
+        assert!(value.decoded.is_none());
+        assert_eq!(value, *"main");
+        assert_eq!(value.raw(), "main");
+
+        let value = HxString::from(String::from("✓ done"));
+        assert_eq!(value, "✓ done");
+        assert_eq!(value.raw().as_bytes(), "✓ done".as_bytes());
+
+        let value = HxString::from("line\nbreak");
+        assert_eq!(value, "line\nbreak");
+        assert_eq!(value.raw(), "line%0Abreak");
+    }
+}
@@ -2,18 +2,22 @@
 
 use http::HeaderName;
 
+pub(crate) const HX_BOOSTED_STR: &str = "hx-boosted";
+
 /// Indicates that the request is via an element using `hx-boost` attribute.
 ///
 /// See <https://htmx.org/attributes/hx-boost/> for more information.
-pub const HX_BOOSTED: HeaderName = HeaderName::from_static("hx-boosted");
+pub const HX_BOOSTED: HeaderName = HeaderName::from_static(HX_BOOSTED_STR);
 
 /// The current URL of the browser.
 pub const HX_CURRENT_URL: HeaderName = HeaderName::from_static("hx-current-url");
 
+pub(crate) const HX_HISTORY_RESTORE_REQUEST_STR: &str = "hx-history-restore-request";
+
 /// `true` if the request is for history restoration after a miss in the local
 /// history cache.
 pub const HX_HISTORY_RESTORE_REQUEST: HeaderName =
-    HeaderName::from_static("hx-history-restore-request");
+    HeaderName::from_static(HX_HISTORY_RESTORE_REQUEST_STR);
 
 /// The user response to an `hx-prompt`
 ///
@@ -0,0 +1,715 @@
+//! A minimal JSON writer, encoding event data and `HX-Location` options
+//! without the `serde` feature, and a reader for the fields of the JSON
+//! objects in `HX-Trigger*` headers.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::{self, Write as _},
+};
+
+/// A JSON value, used as event data or `HX-Location` values.
+///
+/// Covers the payloads htmx handlers usually need, and is available without
+/// the `serde` feature, which allows any `Serialize` type to be used instead.
+///
+/// ```rust
+/// use axum_htmx::{HxEvent, JsonValue};
+///
+/// let event = HxEvent::new_with_json(
+///     "notify",
+///     JsonValue::object([("level", "info".into()), ("count", 3.into())]),
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonValue {
+    /// `null`, also built from `()` and `None`.
+    Null,
+    /// `true` or `false`.
+    Bool(bool),
+    /// A signed integer, built from the signed integer types.
+    Integer(i64),
+    /// An unsigned integer, built from the unsigned integer types.
+    Unsigned(u64),
+    /// A number, written as `null` if it is not finite, as `serde_json` does.
+    Float(f64),
+    /// A string, escaped when it is written.
+    String(String),
+    /// An array, built from a `Vec`.
+    Array(Vec<JsonValue>),
+    /// An object, whose fields are written in order.
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue {
+    /// Creates an object from its fields.
+    pub fn object<K: Into<String>>(fields: impl IntoIterator<Item = (K, JsonValue)>) -> Self {
+        Self::Object(
+            fields
+                .into_iter()
+                .map(|(key, value)| (key.into(), value))
+                .collect(),
+        )
+    }
+}
+
+/// Writes the value as compact JSON.
+impl fmt::Display for JsonValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Null => f.write_str("null"),
+            Self::Bool(value) => write!(f, "{value}"),
+            Self::Integer(value) => write!(f, "{value}"),
+            Self::Unsigned(value) => write!(f, "{value}"),
+            Self::Float(value) if value.is_finite() => write!(f, "{value:?}"),
+            Self::Float(_) => f.write_str("null"),
+            Self::String(value) => write_str(f, value),
+            Self::Array(values) => {
+                f.write_char('[')?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(',')?;
+                    }
+                    write!(f, "{value}")?;
+                }
+                f.write_char(']')
+            }
+            Self::Object(fields) => {
+                f.write_char('{')?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(',')?;
+                    }
+                    write_str(f, key)?;
+                    write!(f, ":{value}")?;
+                }
+                f.write_char('}')
+            }
+        }
+    }
+}
+
+impl From<()> for JsonValue {
+    fn from(_: ()) -> Self {
+        Self::Null
+    }
+}
+
+impl From<bool> for JsonValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+macro_rules! impl_from_number {
+    ($variant:ident, $as:ty => $($ty:ty),+) => {
+        $(
+            impl From<$ty> for JsonValue {
+                fn from(value: $ty) -> Self {
+                    Self::$variant(value as $as)
+                }
+            }
+        )+
+    };
+}
+
+impl_from_number!(Integer, i64 => i8, i16, i32, i64, isize);
+impl_from_number!(Unsigned, u64 => u8, u16, u32, u64, usize);
+impl_from_number!(Float, f64 => f32, f64);
+
+impl From<&str> for JsonValue {
+    fn from(value: &str) -> Self {
+        Self::String(value.to_owned())
+    }
+}
+
+impl From<String> for JsonValue {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+impl<T: Into<JsonValue>> From<Option<T>> for JsonValue {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Self::Null, Into::into)
+    }
+}
+
+impl<T: Into<JsonValue>> From<Vec<T>> for JsonValue {
+    fn from(values: Vec<T>) -> Self {
+        Self::Array(values.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<K: Into<String>, V: Into<JsonValue>, S> From<HashMap<K, V, S>> for JsonValue {
+    fn from(map: HashMap<K, V, S>) -> Self {
+        map.into_iter().collect()
+    }
+}
+
+impl<K: Into<String>, V: Into<JsonValue>> From<BTreeMap<K, V>> for JsonValue {
+    fn from(map: BTreeMap<K, V>) -> Self {
+        map.into_iter().collect()
+    }
+}
+
+impl<K: Into<String>, V: Into<JsonValue>> FromIterator<(K, V)> for JsonValue {
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        Self::Object(
+            iter.into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(feature = "unstable", doc(cfg(feature = "serde")))]
+impl serde::Serialize for JsonValue {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            Self::Null => serializer.serialize_unit(),
+            Self::Bool(value) => serializer.serialize_bool(*value),
+            Self::Integer(value) => serializer.serialize_i64(*value),
+            Self::Unsigned(value) => serializer.serialize_u64(*value),
+            Self::Float(value) => serializer.serialize_f64(*value),
+            Self::String(value) => serializer.serialize_str(value),
+            Self::Array(values) => serializer.collect_seq(values),
+            Self::Object(fields) => {
+                serializer.collect_map(fields.iter().map(|(key, value)| (key, value)))
+            }
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+type RawInner = Box<serde_json::value::RawValue>;
+#[cfg(not(feature = "serde"))]
+type RawInner = Box<str>;
+
+/// Serialized JSON text, written to the headers as-is.
+///
+/// Created from a [`JsonValue`], or with the `serde` feature, from a
+/// `serde_json::value::RawValue`.
+#[derive(Clone)]
+pub struct RawJson(RawInner);
+
+impl RawJson {
+    /// Wraps JSON text produced by this crate.
+    #[cfg(feature = "serde")]
+    pub(crate) fn from_trusted(json: String) -> Self {
+        Self(
+            serde_json::value::RawValue::from_string(json)
+                .expect("the JSON writer produces valid JSON"),
+        )
+    }
+
+    /// Wraps JSON text produced by this crate.
+    #[cfg(not(feature = "serde"))]
+    pub(crate) fn from_trusted(json: String) -> Self {
+        Self(json.into_boxed_str())
+    }
+
+    /// Returns the JSON text.
+    pub fn get(&self) -> &str {
+        #[cfg(feature = "serde")]
+        return self.0.get();
+        #[cfg(not(feature = "serde"))]
+        &self.0
+    }
+}
+
+impl fmt::Debug for RawJson {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("RawJson").field(&self.get()).finish()
+    }
+}
+
+impl fmt::Display for RawJson {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.get())
+    }
+}
+
+impl PartialEq for RawJson {
+    fn eq(&self, other: &Self) -> bool {
+        self.get() == other.get()
+    }
+}
+
+impl Eq for RawJson {}
+
+impl From<JsonValue> for RawJson {
+    fn from(value: JsonValue) -> Self {
+        Self::from_trusted(value.to_string())
+    }
+}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(feature = "unstable", doc(cfg(feature = "serde")))]
+impl From<Box<serde_json::value::RawValue>> for RawJson {
+    fn from(value: Box<serde_json::value::RawValue>) -> Self {
+        Self(value)
+    }
+}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(feature = "unstable", doc(cfg(feature = "serde")))]
+impl From<RawJson> for Box<serde_json::value::RawValue> {
+    fn from(value: RawJson) -> Self {
+        value.0
+    }
+}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(feature = "unstable", doc(cfg(feature = "serde")))]
+impl serde::Serialize for RawJson {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
+
+/// Deserializes any JSON value.
+///
+/// The value goes through a `serde_json::Value`, so that it also works in
+/// flattened structs, and the fields of objects are sorted.
+#[cfg(feature = "serde")]
+#[cfg_attr(feature = "unstable", doc(cfg(feature = "serde")))]
+impl<'de> serde::Deserialize<'de> for RawJson {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value = serde_json::Value::deserialize(deserializer)?;
+
+        serde_json::value::to_raw_value(&value)
+            .map(Self)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Writes a JSON object field by field.
+pub(crate) struct ObjectWriter(String);
+
+impl ObjectWriter {
+    pub(crate) fn new() -> Self {
+        Self(String::from("{"))
+    }
+
+    fn key(&mut self, key: &str) {
+        if self.0.len() > 1 {
+            self.0.push(',');
+        }
+        let _ = write_str(&mut self.0, key);
+        self.0.push(':');
+    }
+
+    /// Writes a string field.
+    pub(crate) fn string(&mut self, key: &str, value: &str) -> &mut Self {
+        self.key(key);
+        let _ = write_str(&mut self.0, value);
+        self
+    }
+
+    /// Writes a string field if `value` is set.
+    pub(crate) fn opt_string(&mut self, key: &str, value: Option<&str>) -> &mut Self {
+        if let Some(value) = value {
+            self.string(key, value);
+        }
+        self
+    }
+
+    /// Writes a field with already serialized JSON.
+    pub(crate) fn raw(&mut self, key: &str, json: &str) -> &mut Self {
+        self.key(key);
+        self.0.push_str(json);
+        self
+    }
+
+    pub(crate) fn finish(mut self) -> String {
+        self.0.push('}');
+        self.0
+    }
+}
+
+/// Writes `value` as a JSON string.
+pub(crate) fn write_str(out: &mut impl fmt::Write, value: &str) -> fmt::Result {
+    out.write_char('"')?;
+
+    let mut start = 0;
+    for (i, c) in value.char_indices() {
+        let escaped = match c {
+            '"' => "\\\"",
+            '\\' => "\\\\",
+            '\n' => "\\n",
+            '\r' => "\\r",
+            '\t' => "\\t",
+            c if (c as u32) < 0x20 => "",
+            _ => continue,
+        };
+
+        out.write_str(&value[start..i])?;
+        if escaped.is_empty() {
+            write!(out, "\\u{:04x}", c as u32)?;
+        } else {
+            out.write_str(escaped)?;
+        }
+        start = i + c.len_utf8();
+    }
+
+    out.write_str(&value[start..])?;
+    out.write_char('"')
+}
+
+/// Returns `true` if the JSON object `object` has `key` at its top level.
+///
+/// `object` is expected to be valid JSON. Keys are compared as written, so a
+/// key spelled with escapes does not match.
+pub(crate) fn has_key(object: &str, key: &str) -> bool {
+    let mut depth = 0usize;
+    let mut expects_key = false;
+    let mut chars = object.char_indices();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '{' => {
+                depth += 1;
+                expects_key = depth == 1;
+            }
+            '[' => depth += 1,
+            '}' | ']' => depth = depth.saturating_sub(1),
+            ',' if depth == 1 => expects_key = true,
+            '"' => {
+                let start = i + 1;
+                let mut end = object.len();
+                while let Some((j, c)) = chars.next() {
+                    match c {
+                        '\\' => {
+                            chars.next();
+                        }
+                        '"' => {
+                            end = j;
+                            break;
+                        }
+                        _ => {}
+                    }
+                }
+                if expects_key && object[start..end] == *key {
+                    return true;
+                }
+                expects_key = false;
+            }
+            _ => {}
+        }
+    }
+
+    false
+}
+
+/// Reads the top-level fields of the JSON object `object`, in the order they
+/// appear, as their decoded keys and the raw JSON text of their values.
+///
+/// Returns `None` if `object` is not a valid JSON object.
+pub(crate) fn object_fields(object: &str) -> Option<Vec<(String, &str)>> {
+    let mut reader = Reader {
+        json: object,
+        pos: 0,
+    };
+    let mut fields = Vec::new();
+
+    reader.expect(b'{')?;
+    if !reader.eat(b'}') {
+        loop {
+            let key = reader.string()?;
+            reader.expect(b':')?;
+            reader.skip_ws();
+            let start = reader.pos;
+            reader.value()?;
+            fields.push((key, &object[start..reader.pos]));
+
+            if reader.eat(b'}') {
+                break;
+            }
+            reader.expect(b',')?;
+        }
+    }
+
+    reader.skip_ws();
+    (reader.pos == object.len()).then_some(fields)
+}
+
+/// Validates JSON text, decoding only the strings that are needed.
+struct Reader<'a> {
+    json: &'a str,
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.json.as_bytes().get(self.pos).copied()
+    }
+
+    fn skip_ws(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    /// Consumes `byte` after optional whitespace, if it is next.
+    fn eat(&mut self, byte: u8) -> bool {
+        self.skip_ws();
+        let found = self.peek() == Some(byte);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, byte: u8) -> Option<()> {
+        self.eat(byte).then_some(())
+    }
+
+    fn digits(&mut self) -> usize {
+        let start = self.pos;
+        while let Some(b'0'..=b'9') = self.peek() {
+            self.pos += 1;
+        }
+        self.pos - start
+    }
+
+    fn value(&mut self) -> Option<()> {
+        match self.peek()? {
+            b'{' => self.nested(b'}'),
+            b'[' => self.nested(b']'),
+            b'"' => self.string().map(drop),
+            b'-' | b'0'..=b'9' => self.number(),
+            _ => ["true", "false", "null"]
+                .into_iter()
+                .find(|literal| self.json[self.pos..].starts_with(literal))
+                .map(|literal| self.pos += literal.len()),
+        }
+    }
+
+    /// Reads an object or an array, whose opening bracket is next.
+    fn nested(&mut self, close: u8) -> Option<()> {
+        self.pos += 1;
+        if self.eat(close) {
+            return Some(());
+        }
+        loop {
+            self.skip_ws();
+            if close == b'}' {
+                self.string()?;
+                self.expect(b':')?;
+                self.skip_ws();
+            }
+            self.value()?;
+            if self.eat(close) {
+                return Some(());
+            }
+            self.expect(b',')?;
+        }
+    }
+
+    fn number(&mut self) -> Option<()> {
+        if self.peek() == Some(b'-') {
+            self.pos += 1;
+        }
+        match self.peek()? {
+            b'0' => self.pos += 1,
+            b'1'..=b'9' => {
+                self.digits();
+            }
+            _ => return None,
+        }
+        if self.peek() == Some(b'.') {
+            self.pos += 1;
+            (self.digits() > 0).then_some(())?;
+        }
+        if let Some(b'e' | b'E') = self.peek() {
+            self.pos += 1;
+            if let Some(b'+' | b'-') = self.peek() {
+                self.pos += 1;
+            }
+            (self.digits() > 0).then_some(())?;
+        }
+        Some(())
+    }
+
+    /// Reads and decodes a string, after optional whitespace.
+    fn string(&mut self) -> Option<String> {
+        self.expect(b'"')?;
+        let mut value = String::new();
+
+        loop {
+            let rest = &self.json[self.pos..];
+            let end = rest.find(['"', '\\'])?;
+            let text = &rest[..end];
+            if text.bytes().any(|b| b < 0x20) {
+                return None;
+            }
+            value.push_str(text);
+            self.pos += end + 1;
+
+            if rest.as_bytes()[end] == b'"' {
+                return Some(value);
+            }
+
+            let escaped = match self.peek()? {
+                b'"' => '"',
+                b'\\' => '\\',
+                b'/' => '/',
+                b'b' => '\u{8}',
+                b'f' => '\u{c}',
+                b'n' => '\n',
+                b'r' => '\r',
+                b't' => '\t',
+                b'u' => {
+                    let high = self.hex()?;
+                    let c = if (0xD800..0xDC00).contains(&high) {
+                        self.json[self.pos + 1..].starts_with("\\u").then_some(())?;
+                        self.pos += 2;
+                        let low = self.hex()?;
+                        (0xDC00..0xE000).contains(&low).then_some(())?;
+                        0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+                    } else {
+                        high
+                    };
+                    value.push(char::from_u32(c)?);
+                    self.pos += 1;
+                    continue;
+                }
+                _ => return None,
+            };
+            value.push(escaped);
+            self.pos += 1;
+        }
+    }
+
+    /// Reads the four hex digits of a `\u` escape, whose `u` is next, leaving
+    /// the position on the last digit.
+    fn hex(&mut self) -> Option<u32> {
+        let digits = self.json.get(self.pos + 1..self.pos + 5)?;
+        if !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        self.pos += 4;
+        u32::from_str_radix(digits, 16).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum_core::response::IntoResponse;
+
+    use super::*;
+    use crate::{HX_LOCATION, HX_TRIGGER, HxEvent, HxLocation, HxResponseTrigger};
+
+    #[test]
+    fn write_values() {
+        let value = JsonValue::object([
+            ("name", "say \"hi\"\n".into()),
+            ("count", 3.into()),
+            ("ratio", 0.5.into()),
+            ("whole", 1.0.into()),
+            ("nan", f64::NAN.into()),
+            ("big", u64::MAX.into()),
+            ("flags", vec![true, false].into()),
+            ("missing", None::<i32>.into()),
+            ("nested", JsonValue::from_iter([("a\u{1}", "b")])),
+        ]);
+
+        assert_eq!(
+            value.to_string(),
+            r#"{"name":"say \"hi\"\n","count":3,"ratio":0.5,"whole":1.0,"nan":null,"big":18446744073709551615,"flags":[true,false],"missing":null,"nested":{"a\u0001":"b"}}"#
+        );
+    }
+
+    #[test]
+    fn top_level_keys() {
+        let object = r#"{"a":{"target":1},"b":["target",{"target":2}],"c\"target":"target"}"#;
+        assert!(!has_key(object, "target"));
+        assert!(has_key(object, "b"));
+        assert!(has_key(r##"{ "id": 1, "target" : "#list" }"##, "target"));
+        assert!(!has_key("{}", "target"));
+    }
+
+    #[test]
+    fn read_object_fields() {
+        let fields = object_fields(
+            r#" { "saved" : null, "caf\u00e9\ud83d\ude00" : {"a": [1, -2.5e3, "}"]}, "n\"": true } "#,
+        )
+        .unwrap();
+        assert_eq!(
+            fields,
+            [
+                ("saved".to_owned(), "null"),
+                ("café😀".to_owned(), r#"{"a": [1, -2.5e3, "}"]}"#),
+                ("n\"".to_owned(), "true"),
+            ]
+        );
+        assert_eq!(object_fields("{}"), Some(Vec::new()));
+
+        for invalid in [
+            "{",
+            "[]",
+            r#"{"a":1,}"#,
+            r#"{"a":01}"#,
+            r#"{"a":1.}"#,
+            r#"{"a":tru}"#,
+            r#"{"a":"\ud83d"}"#,
+            "{\"a\":\"line\nbreak\"}",
+            r#"{"a":1} {}"#,
+        ] {
+            assert_eq!(object_fields(invalid), None, "{invalid}");
+        }
+    }
+
+    #[test]
+    fn merge_without_serde() {
+        let res = (
+            [(HX_TRIGGER, r#"{"saved":{"id":1},"notify":null}"#)],
+            HxResponseTrigger::normal([
+                HxEvent::new("notify"),
+                HxEvent::new_with_json("closed", true),
+            ]),
+            (),
+        )
+            .into_response();
+
+        assert_eq!(
+            res.headers()[HX_TRIGGER],
+            r#"{"saved":{"id":1},"notify":null,"closed":true}"#
+        );
+    }
+
+    #[test]
+    fn headers_without_serde() {
+        let res = (
+            HxResponseTrigger::normal([
+                HxEvent::new("saved"),
+                HxEvent::new_with_json("notify", JsonValue::object([("level", "info".into())])),
+                HxEvent::new_with_json("count", 3).with_target("#badge"),
+            ]),
+            HxLocation::from("/items")
+                .target("#main")
+                .values_json(JsonValue::object([("page", 2.into())])),
+            (),
+        )
+            .into_response();
+
+        assert_eq!(
+            res.headers()[HX_TRIGGER],
+            r##"{"saved":null,"notify":{"level":"info"},"count":{"value":3,"target":"#badge"}}"##
+        );
+        assert_eq!(
+            res.headers()[HX_LOCATION],
+            r##"{"path":"/items","target":"#main","values":{"page":2}}"##
+        );
+    }
+}
@@ -3,23 +3,39 @@
 #![forbid(unsafe_code)]
 #![allow(clippy::too_long_first_doc_paragraph)]
 
+mod encoding;
 mod error;
 pub use error::*;
+mod json;
+pub use json::*;
 
 #[cfg(feature = "auto-vary")]
 #[cfg_attr(feature = "unstable", doc(cfg(feature = "auto-vary")))]
 pub mod auto_vary;
+#[cfg(feature = "event-bus")]
+#[cfg_attr(feature = "unstable", doc(cfg(feature = "event-bus")))]
+pub mod event_bus;
+#[cfg(feature = "macros")]
+#[cfg_attr(feature = "unstable", doc(cfg(feature = "macros")))]
+pub mod export;
 pub mod extractors;
 #[cfg(feature = "guards")]
 #[cfg_attr(feature = "unstable", doc(cfg(feature = "guards")))]
 pub mod guard;
 pub mod headers;
 pub mod responders;
+#[cfg(feature = "typed-header")]
+#[cfg_attr(feature = "unstable", doc(cfg(feature = "typed-header")))]
+pub mod typed_header;
 
 #[cfg(feature = "auto-vary")]
 #[cfg_attr(feature = "unstable", doc(cfg(feature = "auto-vary")))]
 #[doc(inline)]
 pub use auto_vary::*;
+#[cfg(feature = "event-bus")]
+#[cfg_attr(feature = "unstable", doc(cfg(feature = "event-bus")))]
+#[doc(inline)]
+pub use event_bus::*;
 #[doc(inline)]
 pub use extractors::*;
 #[cfg(feature = "guards")]
@@ -30,3 +46,95 @@ pub use guard::*;
 pub use headers::*;
 #[doc(inline)]
 pub use responders::*;
+#[cfg(feature = "typed-header")]
+#[cfg_attr(feature = "unstable", doc(cfg(feature = "typed-header")))]
+#[doc(inline)]
+pub use typed_header::*;
+
+/// Derives `From<T>` or `TryFrom<T>` for [`struct@HxEvent`], turning a type
+/// into a client-side event.
+///
+/// The event is named after the type in kebab-case, so `CartUpdated` triggers
+/// `cart-updated`. A different name can be set with
+/// `#[hx_event(name = "...")]`. Names are checked at compile time, and must
+/// be made of visible ASCII characters other than `,`.
+///
+/// Structs with fields are serialized as the data of the event, and must
+/// implement `serde::Serialize`. Unit structs have no data.
+///
+/// Each variant of an enum is a separate event, named after the variant. The
+/// fields of the variant are serialized like those of a struct, a single
+/// unnamed field being serialized as-is, and their `#[serde]` attributes are
+/// kept. Unit variants have no data. Fields are renamed with
+/// `#[serde(rename_all_fields = "...")]` on the enum, or
+/// `#[serde(rename_all = "...")]` on a variant. The `#[serde]` attributes
+/// that don't apply to the data of a single variant, such as `rename_all` or
+/// `tag` on the enum, are rejected at compile time.
+///
+/// With `#[hx_event(export)]` on the struct or enum, the derive also
+/// implements [`HxEventTypes`](export::HxEventTypes), so that the events can be
+/// [exported](export) as TypeScript typings. The types of the fields of
+/// exported events must implement [`HxShape`](export::HxShape), or have their
+/// shape set to another type with `#[hx_event(shape = Type)]`.
+///
+/// ```rust
+/// use axum_htmx::{HxEvent, HxResponseTrigger};
+/// use serde::Serialize;
+///
+/// #[derive(Serialize, HxEvent)]
+/// struct CartUpdated {
+///     count: u32,
+/// }
+///
+/// #[derive(HxEvent)]
+/// enum Toast {
+///     Saved,
+///     #[hx_event(name = "toast:error")]
+///     Failed { message: String },
+/// }
+///
+/// async fn add_to_cart() -> (HxResponseTrigger, &'static str) {
+///     let events: [HxEvent; 2] = [
+///         CartUpdated { count: 3 }.into(),
+///         Toast::Saved.into(),
+///     ];
+///
+///     (HxResponseTrigger::normal(events), "Added!")
+/// }
+/// ```
+///
+/// `From<T>` is only implemented for `HxEvent` when the fields are known to
+/// always serialize, i.e. they are built from primitives, strings, and the
+/// collections of the standard library, maps having string or integer keys.
+/// Other types, such as the types of the application, implement `TryFrom<T>`,
+/// returning the `serde_json::Error` if the data fails to serialize. The
+/// derive also adds a `try_into_hx_event()` method to the type, doing the
+/// same.
+#[cfg(feature = "macros")]
+#[cfg_attr(feature = "unstable", doc(cfg(feature = "macros")))]
+pub use axum_htmx_macros::HxEvent;
+
+/// Derives [`HxShape`](export::HxShape), describing the data of events.
+///
+/// Structs are described as serde serializes them, following the
+/// `rename_all`, `transparent`, `rename`, `skip`, `skip_serializing_if`, and
+/// `flatten` attributes. Enums can only have unit variants, described as
+/// strings.
+///
+/// The types of the fields must implement `HxShape`, or have their shape set
+/// to another type with `#[hx_event(shape = Type)]`, such as `String` for a
+/// type serialized as a string.
+#[cfg(feature = "macros")]
+#[cfg_attr(feature = "unstable", doc(cfg(feature = "macros")))]
+pub use axum_htmx_macros::HxShape;
+
+// Lets the derive macros refer to `::axum_htmx` in the tests of this crate.
+#[cfg(all(test, feature = "macros"))]
+extern crate self as axum_htmx;
+
+#[cfg(feature = "macros")]
+#[doc(hidden)]
+pub mod __private {
+    pub use serde;
+    pub use serde_json;
+}
@@ -1,14 +1,22 @@
 //! Axum responses for htmx response headers.
 
-use std::convert::Infallible;
+use std::{borrow::Cow, convert::Infallible, fmt, str::FromStr};
 
 use axum_core::response::{IntoResponseParts, ResponseParts};
-use http::HeaderValue;
+use http::{HeaderValue, Uri};
 
-use crate::{HxError, headers};
+use crate::{
+    HxDecodeError, HxError, SelectorError, SwapParseError,
+    encoding::{css_escape, url_header_value},
+    headers,
+};
 
 mod location;
 pub use location::*;
+mod selector;
+pub use selector::*;
+mod swap;
+pub use swap::*;
 mod trigger;
 pub use trigger::*;
 mod vary;
@@ -22,26 +30,68 @@ const HX_SWAP_BEFORE_END: &str = "beforeend";
 const HX_SWAP_AFTER_END: &str = "afterend";
 const HX_SWAP_DELETE: &str = "delete";
 const HX_SWAP_NONE: &str = "none";
+const HX_SWAP_TEXT_CONTENT: &str = "textContent";
+const HX_SWAP_MORPH_OUTER_HTML: &str = "morph:outerHTML";
+const HX_SWAP_MORPH_INNER_HTML: &str = "morph:innerHTML";
+
+/// Returns the value of a response header, which must be visible ASCII.
+pub(crate) fn header_str(value: &HeaderValue) -> Result<&str, HxDecodeError> {
+    value
+        .to_str()
+        .map_err(|_| HxDecodeError::InvalidHeaderValue)
+}
 
 /// The `HX-Push-Url` header.
 ///
-/// Pushes a new url into the history stack.
+/// Pushes a new url into the history stack, or prevents the browser history
+/// from being updated.
 ///
-/// Will fail if the supplied Uri contains characters that are not visible ASCII
-/// (32-127).
+/// The url is percent-encoded where it contains characters that are not
+/// visible ASCII (32-127), so this responder will never fail.
 ///
 /// See <https://htmx.org/headers/hx-push-url/> for more information.
-#[derive(Debug, Clone)]
-pub struct HxPushUrl(pub String);
+///
+/// ```rust
+/// use axum_htmx::HxPushUrl;
+///
+/// let url = HxPushUrl::url("/projects/café");
+/// let prevent = HxPushUrl::prevent();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HxPushUrl {
+    /// Pushes the url into the history stack.
+    Url(String),
+    /// Leaves the browser history untouched, sent as `false`.
+    Prevent,
+}
+
+impl HxPushUrl {
+    /// Pushes `url` into the history stack, from a string, an `http::Uri`, or
+    /// any other `Display` type such as the typed paths of `axum-extra`.
+    pub fn url(url: impl fmt::Display) -> Self {
+        Self::Url(url.to_string())
+    }
+
+    /// Leaves the browser history untouched.
+    pub fn prevent() -> Self {
+        Self::Prevent
+    }
+
+    /// Returns the value of the header, before percent-encoding.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Url(url) => url,
+            Self::Prevent => "false",
+        }
+    }
+}
 
 impl IntoResponseParts for HxPushUrl {
-    type Error = HxError;
+    type Error = Infallible;
 
     fn into_response_parts(self, mut res: ResponseParts) -> Result<ResponseParts, Self::Error> {
-        res.headers_mut().insert(
-            headers::HX_PUSH_URL,
-            HeaderValue::from_maybe_shared(self.0)?,
-        );
+        res.headers_mut()
+            .insert(headers::HX_PUSH_URL, url_header_value(self.as_str()));
 
         Ok(res)
     }
@@ -49,7 +99,31 @@ impl IntoResponseParts for HxPushUrl {
 
 impl<'a> From<&'a str> for HxPushUrl {
     fn from(value: &'a str) -> Self {
-        Self(value.to_string())
+        Self::Url(value.to_string())
+    }
+}
+
+impl From<String> for HxPushUrl {
+    fn from(value: String) -> Self {
+        Self::Url(value)
+    }
+}
+
+impl From<Uri> for HxPushUrl {
+    fn from(value: Uri) -> Self {
+        Self::url(value)
+    }
+}
+
+/// Decodes either `false` or a url.
+impl TryFrom<&HeaderValue> for HxPushUrl {
+    type Error = HxDecodeError;
+
+    fn try_from(value: &HeaderValue) -> Result<Self, Self::Error> {
+        Ok(match header_str(value)?.trim() {
+            "false" => Self::Prevent,
+            url => Self::Url(url.to_string()),
+        })
     }
 }
 
@@ -57,19 +131,17 @@ impl<'a> From<&'a str> for HxPushUrl {
 ///
 /// Can be used to do a client-side redirect to a new location.
 ///
-/// Will fail if the supplied Uri contains characters that are not visible ASCII
-/// (32-127).
+/// The url is percent-encoded where it contains characters that are not
+/// visible ASCII (32-127), so this responder will never fail.
 #[derive(Debug, Clone)]
 pub struct HxRedirect(pub String);
 
 impl IntoResponseParts for HxRedirect {
-    type Error = HxError;
+    type Error = Infallible;
 
     fn into_response_parts(self, mut res: ResponseParts) -> Result<ResponseParts, Self::Error> {
-        res.headers_mut().insert(
-            headers::HX_REDIRECT,
-            HeaderValue::from_maybe_shared(self.0)?,
-        );
+        res.headers_mut()
+            .insert(headers::HX_REDIRECT, url_header_value(&self.0));
 
         Ok(res)
     }
@@ -81,6 +153,14 @@ impl<'a> From<&'a str> for HxRedirect {
     }
 }
 
+impl TryFrom<&HeaderValue> for HxRedirect {
+    type Error = HxDecodeError;
+
+    fn try_from(value: &HeaderValue) -> Result<Self, Self::Error> {
+        header_str(value).map(|value| Self(value.to_string()))
+    }
+}
+
 /// The `HX-Refresh`header.
 ///
 /// If set to `true` the client-side will do a full refresh of the page.
@@ -95,6 +175,18 @@ impl From<bool> for HxRefresh {
     }
 }
 
+impl TryFrom<&HeaderValue> for HxRefresh {
+    type Error = HxDecodeError;
+
+    fn try_from(value: &HeaderValue) -> Result<Self, Self::Error> {
+        match header_str(value)?.trim() {
+            "true" => Ok(Self(true)),
+            "false" => Ok(Self(false)),
+            value => Err(HxDecodeError::InvalidBool(value.to_string())),
+        }
+    }
+}
+
 impl IntoResponseParts for HxRefresh {
     type Error = Infallible;
 
@@ -114,23 +206,55 @@ impl IntoResponseParts for HxRefresh {
 
 /// The `HX-Replace-Url` header.
 ///
-/// Replaces the currelt URL in the location bar.
+/// Replaces the current url in the location bar, or prevents the browser
+/// history from being updated.
 ///
-/// Will fail if the supplied Uri contains characters that are not visible ASCII
-/// (32-127).
+/// The url is percent-encoded where it contains characters that are not
+/// visible ASCII (32-127), so this responder will never fail.
 ///
 /// See <https://htmx.org/headers/hx-replace-url/> for more information.
-#[derive(Debug, Clone)]
-pub struct HxReplaceUrl(pub String);
+///
+/// ```rust
+/// use axum_htmx::HxReplaceUrl;
+///
+/// let url = HxReplaceUrl::url("/projects/café");
+/// let prevent = HxReplaceUrl::prevent();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HxReplaceUrl {
+    /// Replaces the current url with this one.
+    Url(String),
+    /// Leaves the browser history untouched, sent as `false`.
+    Prevent,
+}
+
+impl HxReplaceUrl {
+    /// Replaces the current url with `url`, from a string, an `http::Uri`, or
+    /// any other `Display` type such as the typed paths of `axum-extra`.
+    pub fn url(url: impl fmt::Display) -> Self {
+        Self::Url(url.to_string())
+    }
+
+    /// Leaves the browser history untouched.
+    pub fn prevent() -> Self {
+        Self::Prevent
+    }
+
+    /// Returns the value of the header, before percent-encoding.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Url(url) => url,
+            Self::Prevent => "false",
+        }
+    }
+}
 
 impl IntoResponseParts for HxReplaceUrl {
-    type Error = HxError;
+    type Error = Infallible;
 
     fn into_response_parts(self, mut res: ResponseParts) -> Result<ResponseParts, Self::Error> {
-        res.headers_mut().insert(
-            headers::HX_REPLACE_URL,
-            HeaderValue::from_maybe_shared(self.0)?,
-        );
+        res.headers_mut()
+            .insert(headers::HX_REPLACE_URL, url_header_value(self.as_str()));
 
         Ok(res)
     }
@@ -138,23 +262,50 @@ impl IntoResponseParts for HxReplaceUrl {
 
 impl<'a> From<&'a str> for HxReplaceUrl {
     fn from(value: &'a str) -> Self {
-        Self(value.to_string())
+        Self::Url(value.to_string())
+    }
+}
+
+impl From<String> for HxReplaceUrl {
+    fn from(value: String) -> Self {
+        Self::Url(value)
+    }
+}
+
+impl From<Uri> for HxReplaceUrl {
+    fn from(value: Uri) -> Self {
+        Self::url(value)
+    }
+}
+
+/// Decodes either `false` or a url.
+impl TryFrom<&HeaderValue> for HxReplaceUrl {
+    type Error = HxDecodeError;
+
+    fn try_from(value: &HeaderValue) -> Result<Self, Self::Error> {
+        Ok(match header_str(value)?.trim() {
+            "false" => Self::Prevent,
+            url => Self::Url(url.to_string()),
+        })
     }
 }
 
 /// The `HX-Reswap` header.
 ///
-/// Allows you to specidy how the response will be swapped.
+/// Allows you to specidy how the response will be swapped, either with a bare
+/// [`SwapOption`] or with a [`Swap`] carrying `hx-swap` modifiers.
 ///
-/// This responder will never fail.
-#[derive(Debug, Copy, Clone)]
-pub struct HxReswap(pub SwapOption);
+/// This responder will never fail. Characters of selectors that are not
+/// visible ASCII (32-127) are written as CSS escapes.
+#[derive(Debug, Clone)]
+pub struct HxReswap(pub Swap);
 
 impl IntoResponseParts for HxReswap {
     type Error = Infallible;
 
     fn into_response_parts(self, mut res: ResponseParts) -> Result<ResponseParts, Self::Error> {
-        res.headers_mut().insert(headers::HX_RESWAP, self.0.into());
+        res.headers_mut()
+            .insert(headers::HX_RESWAP, HeaderValue::from(&self.0));
 
         Ok(res)
     }
@@ -162,20 +313,44 @@ impl IntoResponseParts for HxReswap {
 
 impl From<SwapOption> for HxReswap {
     fn from(value: SwapOption) -> Self {
+        Self(value.into())
+    }
+}
+
+impl From<Swap> for HxReswap {
+    fn from(value: Swap) -> Self {
         Self(value)
     }
 }
 
+impl TryFrom<&HeaderValue> for HxReswap {
+    type Error = HxDecodeError;
+
+    fn try_from(value: &HeaderValue) -> Result<Self, Self::Error> {
+        Swap::try_from(value).map(Self)
+    }
+}
+
 /// The `HX-Retarget` header.
 ///
 /// A CSS selector that updates the target of the content update to a different
-/// element on the page.
+/// element on the page. A [`Selector`] checks the selector when it is built.
 ///
 /// Will fail if the supplied String contains characters that are not visible
 /// ASCII (32-127).
 #[derive(Debug, Clone)]
 pub struct HxRetarget(pub String);
 
+impl HxRetarget {
+    /// Decodes the value of a received header.
+    ///
+    /// This is not a `TryFrom<&HeaderValue>` implementation, as it would
+    /// conflict with the blanket `From<T: Into<String>>` implementation.
+    pub fn from_header_value(value: &HeaderValue) -> Result<Self, HxDecodeError> {
+        header_str(value).map(|value| Self(value.to_string()))
+    }
+}
+
 impl IntoResponseParts for HxRetarget {
     type Error = HxError;
 
@@ -195,16 +370,33 @@ impl<T: Into<String>> From<T> for HxRetarget {
     }
 }
 
+impl From<Selector> for HxRetarget {
+    fn from(selector: Selector) -> Self {
+        Self(selector.into_string())
+    }
+}
+
 /// The `HX-Reselect` header.
 ///
 /// A CSS selector that allows you to choose which part of the response is used
 /// to be swapped in. Overrides an existing hx-select on the triggering element.
+/// A [`Selector`] checks the selector when it is built.
 ///
 /// Will fail if the supplied String contains characters that are not visible
 /// ASCII (32-127).
 #[derive(Debug, Clone)]
 pub struct HxReselect(pub String);
 
+impl HxReselect {
+    /// Decodes the value of a received header.
+    ///
+    /// This is not a `TryFrom<&HeaderValue>` implementation, as it would
+    /// conflict with the blanket `From<T: Into<String>>` implementation.
+    pub fn from_header_value(value: &HeaderValue) -> Result<Self, HxDecodeError> {
+        header_str(value).map(|value| Self(value.to_string()))
+    }
+}
+
 impl IntoResponseParts for HxReselect {
     type Error = HxError;
 
@@ -224,14 +416,32 @@ impl<T: Into<String>> From<T> for HxReselect {
     }
 }
 
+/// Fails for the extended targets of htmx, such as `closest .card`, as
+/// `hx-select` only accepts CSS selectors.
+impl TryFrom<Selector> for HxReselect {
+    type Error = SelectorError;
+
+    fn try_from(selector: Selector) -> Result<Self, Self::Error> {
+        selector.into_css().map(Self)
+    }
+}
+
 /// Values of the `hx-swap` attribute.
+///
+/// Besides the built-in swap styles, strategies registered by extensions can be
+/// used through [`Custom`](Self::Custom), preferably built with
+/// [`SwapOption::custom`], which checks the name.
 // serde::Serialize is implemented in responders/serde.rs
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum SwapOption {
     /// Replace the inner html of the target element.
     InnerHtml,
     /// Replace the entire target element with the response.
     OuterHtml,
+    /// Replace the text content of the target element, without parsing the
+    /// response as HTML.
+    TextContent,
     /// Insert the response before the target element.
     BeforeBegin,
     /// Insert the response before the first child of the target element.
@@ -245,6 +455,103 @@ pub enum SwapOption {
     /// Does not append content from response (out of band items will still be
     /// processed).
     None,
+    /// Morph the entire target element into the response, with the
+    /// [idiomorph](https://github.com/bigskysoftware/idiomorph) extension.
+    MorphOuterHtml,
+    /// Morph the children of the target element into the response, with the
+    /// [idiomorph](https://github.com/bigskysoftware/idiomorph) extension.
+    MorphInnerHtml,
+    /// A swap style registered by an extension.
+    ///
+    /// Custom names of built-in styles are decoded as the built-in variant.
+    Custom(Cow<'static, str>),
+}
+
+impl SwapOption {
+    /// Returns the name of the swap style, as written in `hx-swap`.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::InnerHtml => HX_SWAP_INNER_HTML,
+            Self::OuterHtml => HX_SWAP_OUTER_HTML,
+            Self::TextContent => HX_SWAP_TEXT_CONTENT,
+            Self::BeforeBegin => HX_SWAP_BEFORE_BEGIN,
+            Self::AfterBegin => HX_SWAP_AFTER_BEGIN,
+            Self::BeforeEnd => HX_SWAP_BEFORE_END,
+            Self::AfterEnd => HX_SWAP_AFTER_END,
+            Self::Delete => HX_SWAP_DELETE,
+            Self::None => HX_SWAP_NONE,
+            Self::MorphOuterHtml => HX_SWAP_MORPH_OUTER_HTML,
+            Self::MorphInnerHtml => HX_SWAP_MORPH_INNER_HTML,
+            Self::Custom(name) => name,
+        }
+    }
+
+    /// Creates a swap style registered by an extension, checking that its
+    /// name is a single style made of visible ASCII characters.
+    ///
+    /// Names of built-in styles return the built-in variant.
+    ///
+    /// ```rust
+    /// use axum_htmx::SwapOption;
+    ///
+    /// assert_eq!(
+    ///     SwapOption::custom("morph"),
+    ///     Ok(SwapOption::Custom("morph".into()))
+    /// );
+    /// assert_eq!(SwapOption::custom("outerHTML"), Ok(SwapOption::OuterHtml));
+    /// assert!(SwapOption::custom("morph\u{1}").is_err());
+    /// ```
+    pub fn custom(name: impl Into<Cow<'static, str>>) -> Result<Self, SwapParseError> {
+        let name = name.into();
+
+        match name.parse()? {
+            Self::Custom(parsed) if parsed == name => Ok(Self::Custom(name)),
+            option => Ok(option),
+        }
+    }
+}
+
+/// Parses a bare swap style, as written in `hx-swap`.
+///
+/// Unknown names are kept as [`Custom`](SwapOption::Custom) styles, as
+/// extensions can register any name.
+///
+/// ```rust
+/// use axum_htmx::SwapOption;
+///
+/// assert_eq!("outerHTML".parse(), Ok(SwapOption::OuterHtml));
+/// assert_eq!(
+///     "morph".parse(),
+///     Ok(SwapOption::Custom("morph".into()))
+/// );
+/// assert!("outerHTML swap:1s".parse::<SwapOption>().is_err());
+/// ```
+impl FromStr for SwapOption {
+    type Err = SwapParseError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Ok(match name.trim() {
+            HX_SWAP_INNER_HTML => Self::InnerHtml,
+            HX_SWAP_OUTER_HTML => Self::OuterHtml,
+            HX_SWAP_TEXT_CONTENT => Self::TextContent,
+            HX_SWAP_BEFORE_BEGIN => Self::BeforeBegin,
+            HX_SWAP_AFTER_BEGIN => Self::AfterBegin,
+            HX_SWAP_BEFORE_END => Self::BeforeEnd,
+            HX_SWAP_AFTER_END => Self::AfterEnd,
+            HX_SWAP_DELETE => Self::Delete,
+            HX_SWAP_NONE => Self::None,
+            HX_SWAP_MORPH_OUTER_HTML => Self::MorphOuterHtml,
+            HX_SWAP_MORPH_INNER_HTML => Self::MorphInnerHtml,
+            "" => return Err(SwapParseError::Empty),
+            name if name.contains(char::is_whitespace) => {
+                return Err(SwapParseError::NotASingleStyle(name.to_string()));
+            }
+            name if !name.bytes().all(|b| b.is_ascii_graphic()) => {
+                return Err(SwapParseError::InvalidStyle(name.to_string()));
+            }
+            name => Self::Custom(name.to_string().into()),
+        })
+    }
 }
 
 // can be removed  and automatically derived when
@@ -269,21 +576,60 @@ impl ::serde::Serialize for SwapOption {
             Self::AfterEnd => serializer.serialize_unit_variant(UNIT_NAME, 5, HX_SWAP_AFTER_END),
             Self::Delete => serializer.serialize_unit_variant(UNIT_NAME, 6, HX_SWAP_DELETE),
             Self::None => serializer.serialize_unit_variant(UNIT_NAME, 7, HX_SWAP_NONE),
+            // Appended after the original styles, so that their indices are kept.
+            Self::TextContent => {
+                serializer.serialize_unit_variant(UNIT_NAME, 8, HX_SWAP_TEXT_CONTENT)
+            }
+            Self::MorphOuterHtml => {
+                serializer.serialize_unit_variant(UNIT_NAME, 9, HX_SWAP_MORPH_OUTER_HTML)
+            }
+            Self::MorphInnerHtml => {
+                serializer.serialize_unit_variant(UNIT_NAME, 10, HX_SWAP_MORPH_INNER_HTML)
+            }
+            Self::Custom(name) => serializer.serialize_str(name),
         }
     }
 }
 
+#[cfg(feature = "serde")]
+impl<'de> ::serde::Deserialize<'de> for SwapOption {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: ::serde::Deserializer<'de>,
+    {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(::serde::de::Error::custom)
+    }
+}
+
+impl TryFrom<&HeaderValue> for SwapOption {
+    type Error = HxDecodeError;
+
+    fn try_from(value: &HeaderValue) -> Result<Self, Self::Error> {
+        Ok(header_str(value)?.parse()?)
+    }
+}
+
+/// A [`Custom`](SwapOption::Custom) style built directly, rather than with
+/// [`SwapOption::custom`] or parsed, is written with CSS escapes for the
+/// characters that cannot be sent in a header.
 impl From<SwapOption> for HeaderValue {
     fn from(value: SwapOption) -> Self {
         match value {
             SwapOption::InnerHtml => HeaderValue::from_static(HX_SWAP_INNER_HTML),
             SwapOption::OuterHtml => HeaderValue::from_static(HX_SWAP_OUTER_HTML),
+            SwapOption::TextContent => HeaderValue::from_static(HX_SWAP_TEXT_CONTENT),
             SwapOption::BeforeBegin => HeaderValue::from_static(HX_SWAP_BEFORE_BEGIN),
             SwapOption::AfterBegin => HeaderValue::from_static(HX_SWAP_AFTER_BEGIN),
             SwapOption::BeforeEnd => HeaderValue::from_static(HX_SWAP_BEFORE_END),
             SwapOption::AfterEnd => HeaderValue::from_static(HX_SWAP_AFTER_END),
             SwapOption::Delete => HeaderValue::from_static(HX_SWAP_DELETE),
             SwapOption::None => HeaderValue::from_static(HX_SWAP_NONE),
+            SwapOption::MorphOuterHtml => HeaderValue::from_static(HX_SWAP_MORPH_OUTER_HTML),
+            SwapOption::MorphInnerHtml => HeaderValue::from_static(HX_SWAP_MORPH_INNER_HTML),
+            SwapOption::Custom(name) => HeaderValue::try_from(css_escape(&name).into_owned())
+                .expect("escaped swap styles are visible ASCII"),
         }
     }
 }
@@ -1,17 +1,26 @@
+use std::{borrow::Cow, convert::Infallible};
+
 use axum_core::response::{IntoResponseParts, ResponseParts};
 use http::HeaderValue;
 
-use crate::{HxError, headers};
+use crate::{
+    HxDecodeError, JsonValue, RawJson,
+    encoding::{ascii_json, percent_encode},
+    headers,
+    json::ObjectWriter,
+    responders::header_str,
+};
 
 /// The `HX-Location` header.
 ///
 /// This response header can be used to trigger a client side redirection
 /// without reloading the whole page. If you intend to redirect to a specific
-/// target on the page, you must enable the `serde` feature flag and specify
-/// [`LocationOptions`].
+/// target on the page, specify [`LocationOptions`], usually through the
+/// builder methods such as [`HxLocation::target`].
 ///
-/// Will fail if the supplied uri contains characters that are not visible ASCII
-/// (32-127).
+/// Characters of the uri that are not visible ASCII (32-127) are
+/// percent-encoded, and those of the options are escaped in the JSON value,
+/// so this responder will never fail.
 ///
 /// See <https://htmx.org/headers/hx-location/> for more information.
 #[derive(Debug, Clone)]
@@ -19,8 +28,6 @@ pub struct HxLocation {
     /// Uri of the new location.
     pub uri: String,
     /// Extra options.
-    #[cfg(feature = "serde")]
-    #[cfg_attr(feature = "unstable", doc(cfg(feature = "serde")))]
     pub options: LocationOptions,
 }
 
@@ -29,15 +36,12 @@ impl HxLocation {
     #[allow(clippy::should_implement_trait)]
     pub fn from_str(uri: impl AsRef<str>) -> Self {
         Self {
-            #[cfg(feature = "serde")]
             options: LocationOptions::default(),
             uri: uri.as_ref().to_string(),
         }
     }
 
     /// Parses `uri` and sets it as location with additional options.
-    #[cfg(feature = "serde")]
-    #[cfg_attr(feature = "unstable", doc(cfg(feature = "serde")))]
     pub fn from_str_with_options(uri: impl AsRef<str>, options: LocationOptions) -> Self {
         Self {
             options,
@@ -45,25 +49,138 @@ impl HxLocation {
         }
     }
 
+    /// Sets the source element of the request.
+    pub fn source(mut self, source: impl Into<String>) -> Self {
+        self.options.source = Some(source.into());
+        self
+    }
+
+    /// Sets the event that "triggered" the request.
+    pub fn event(mut self, event: impl Into<String>) -> Self {
+        self.options.event = Some(event.into());
+        self
+    }
+
+    /// Sets the callback that will handle the response HTML.
+    pub fn handler(mut self, handler: impl Into<String>) -> Self {
+        self.options.handler = Some(handler.into());
+        self
+    }
+
+    /// Sets the target to swap the response into.
+    ///
+    /// ```rust
+    /// use axum_htmx::{HxLocation, SwapOption};
+    ///
+    /// let location = HxLocation::from("/cart")
+    ///     .target("#main")
+    ///     .swap(SwapOption::OuterHtml)
+    ///     .push(false);
+    /// ```
+    pub fn target(mut self, target: impl Into<String>) -> Self {
+        self.options.target = Some(target.into());
+        self
+    }
+
+    /// Sets how the response will be swapped in relative to the target.
+    pub fn swap(mut self, swap: impl Into<crate::Swap>) -> Self {
+        self.options.swap = Some(swap.into());
+        self
+    }
+
+    /// Sets the values to submit with the request, from any serializable
+    /// type such as a struct or a `HashMap`.
+    ///
+    /// Fails if `values` cannot be serialized.
     #[cfg(feature = "serde")]
-    fn into_header_with_options(self) -> Result<String, HxError> {
-        if self.options.is_default() {
-            return Ok(self.uri.to_string());
+    #[cfg_attr(feature = "unstable", doc(cfg(feature = "serde")))]
+    pub fn values<T: serde::Serialize>(mut self, values: T) -> Result<Self, serde_json::Error> {
+        self.options.values = Some(serde_json::value::to_raw_value(&values)?.into());
+        Ok(self)
+    }
+
+    /// Sets the values to submit with the request, built from strings,
+    /// numbers, booleans, and maps, without the `serde` feature.
+    pub fn values_json(mut self, values: impl Into<JsonValue>) -> Self {
+        self.options.values = Some(values.into().into());
+        self
+    }
+
+    /// Sets the headers to submit with the request.
+    pub fn headers<K, V>(mut self, headers: impl IntoIterator<Item = (K, V)>) -> Self
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        let mut object = ObjectWriter::new();
+        for (name, value) in headers {
+            object.string(&name.into(), &value.into());
         }
+        self.options.headers = Some(RawJson::from_trusted(object.finish()));
+        self
+    }
 
-        #[derive(::serde::Serialize)]
-        struct LocWithOpts {
-            path: String,
-            #[serde(flatten)]
-            opts: LocationOptions,
+    /// Sets the content to select from the response.
+    pub fn select(mut self, select: impl Into<String>) -> Self {
+        self.options.select = Some(select.into());
+        self
+    }
+
+    /// Sets whether and which url is pushed into the browser history.
+    pub fn push(mut self, push: impl Into<HistoryUpdate>) -> Self {
+        self.options.push = Some(push.into());
+        self
+    }
+
+    /// Sets whether and which url replaces the current one in the browser
+    /// history.
+    pub fn replace(mut self, replace: impl Into<HistoryUpdate>) -> Self {
+        self.options.replace = Some(replace.into());
+        self
+    }
+
+    pub(crate) fn into_header_with_options(self) -> String {
+        let path = percent_encode(&self.uri);
+        if self.options.is_default() {
+            return path.into_owned();
         }
 
-        let loc_with_opts = LocWithOpts {
-            path: self.uri.to_string(),
-            opts: self.options,
-        };
+        let LocationOptions {
+            source,
+            event,
+            handler,
+            target,
+            swap,
+            values,
+            headers,
+            select,
+            push,
+            replace,
+            non_exhaustive: (),
+        } = &self.options;
 
-        Ok(serde_json::to_string(&loc_with_opts)?)
+        let mut object = ObjectWriter::new();
+        object
+            .string("path", &path)
+            .opt_string("source", source.as_deref())
+            .opt_string("event", event.as_deref())
+            .opt_string("handler", handler.as_deref())
+            .opt_string("target", target.as_deref())
+            .opt_string("swap", swap.as_ref().map(ToString::to_string).as_deref());
+        for (key, json) in [("values", values), ("headers", headers)] {
+            if let Some(json) = json {
+                object.raw(key, json.get());
+            }
+        }
+        object
+            .opt_string("select", select.as_deref())
+            .opt_string("push", push.as_ref().map(HistoryUpdate::encoded).as_deref())
+            .opt_string(
+                "replace",
+                replace.as_ref().map(HistoryUpdate::encoded).as_deref(),
+            );
+
+        ascii_json(&object.finish()).into_owned()
     }
 }
 
@@ -73,26 +190,89 @@ impl<'a> From<&'a str> for HxLocation {
     }
 }
 
-#[cfg(feature = "serde")]
-#[cfg_attr(feature = "unstable", doc(cfg(feature = "serde")))]
 impl<'a> From<(&'a str, LocationOptions)> for HxLocation {
     fn from((uri, options): (&'a str, LocationOptions)) -> Self {
         Self::from_str_with_options(uri, options)
     }
 }
 
-impl IntoResponseParts for HxLocation {
-    type Error = HxError;
+/// Decodes an `HX-Location` header, either a bare path or a JSON object with a
+/// `path` and additional options.
+///
+/// JSON values can only be decoded with the `serde` feature enabled.
+impl TryFrom<&HeaderValue> for HxLocation {
+    type Error = HxDecodeError;
+
+    fn try_from(value: &HeaderValue) -> Result<Self, Self::Error> {
+        let value = header_str(value)?.trim();
+
+        if !value.starts_with('{') {
+            return Ok(Self::from_str(value));
+        }
 
-    fn into_response_parts(self, mut res: ResponseParts) -> Result<ResponseParts, Self::Error> {
         #[cfg(feature = "serde")]
-        let header = self.into_header_with_options()?;
+        return Ok(serde_json::from_str(value)?);
         #[cfg(not(feature = "serde"))]
-        let header = self.uri.to_string();
+        Err(HxDecodeError::UnsupportedJson)
+    }
+}
+
+/// Deserializes either a bare path or an object with a `path` and additional
+/// options, the two forms of the `HX-Location` header.
+#[cfg(feature = "serde")]
+#[cfg_attr(feature = "unstable", doc(cfg(feature = "serde")))]
+impl<'de> serde::Deserialize<'de> for HxLocation {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(serde::Deserialize)]
+        struct LocWithOpts {
+            path: String,
+            #[serde(flatten)]
+            opts: LocationOptions,
+        }
+
+        struct Visitor;
+
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = HxLocation;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a path or an object with a path and options")
+            }
+
+            fn visit_str<E>(self, path: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(HxLocation::from_str(path))
+            }
+
+            fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::MapAccess<'de>,
+            {
+                let LocWithOpts { path, opts } = serde::Deserialize::deserialize(
+                    serde::de::value::MapAccessDeserializer::new(map),
+                )?;
+                Ok(HxLocation::from_str_with_options(path, opts))
+            }
+        }
+
+        deserializer.deserialize_any(Visitor)
+    }
+}
+
+impl IntoResponseParts for HxLocation {
+    type Error = Infallible;
+
+    fn into_response_parts(self, mut res: ResponseParts) -> Result<ResponseParts, Self::Error> {
+        let header = self.into_header_with_options();
 
         res.headers_mut().insert(
             headers::HX_LOCATION,
-            HeaderValue::from_maybe_shared(header)?,
+            HeaderValue::try_from(header).expect("the location is encoded as visible ASCII"),
         );
 
         Ok(res)
@@ -110,39 +290,51 @@ impl IntoResponseParts for HxLocation {
 /// - `headers` - headers to submit with the request
 /// - `select` - allows you to select the content you want swapped from a
 ///   response
-#[cfg(feature = "serde")]
-#[cfg_attr(feature = "unstable", doc(cfg(feature = "serde")))]
-#[derive(Debug, Clone, serde::Serialize, Default)]
+/// - `push` - whether and which url is pushed into the browser history
+/// - `replace` - whether and which url replaces the current one in the
+///   browser history
+///
+/// The options are usually set through the builder methods of [`HxLocation`],
+/// such as [`HxLocation::target`].
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LocationOptions {
     /// The source element of the request.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub source: Option<String>,
     /// An event that "triggered" the request.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub event: Option<String>,
     /// A callback that will handle the response HTML.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub handler: Option<String>,
     /// The target to swap the response into.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub target: Option<String>,
     /// How the response will be swapped in relative to the target.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub swap: Option<crate::SwapOption>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub swap: Option<crate::Swap>,
     /// Values to submit with the request.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub values: Option<serde_json::Value>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub values: Option<RawJson>,
     /// Headers to submit with the request.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub headers: Option<serde_json::Value>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub headers: Option<RawJson>,
+    /// The content to select from the response.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub select: Option<String>,
+    /// Whether and which url is pushed into the browser history.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub push: Option<HistoryUpdate>,
+    /// Whether and which url replaces the current one in the browser history.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub replace: Option<HistoryUpdate>,
     // Hacky way of making this struct non-exhaustive.
     // See <https://rust-lang.github.io/rfcs/2008-non-exhaustive.html> and <https://github.com/robertwayne/axum-htmx/issues/29> for reasoning.
-    #[serde(skip)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub non_exhaustive: (),
 }
 
-#[cfg(feature = "serde")]
-#[cfg_attr(feature = "unstable", doc(cfg(feature = "serde")))]
 impl LocationOptions {
     pub(super) fn is_default(&self) -> bool {
         let Self {
@@ -153,6 +345,9 @@ impl LocationOptions {
             swap: None,
             values: None,
             headers: None,
+            select: None,
+            push: None,
+            replace: None,
             non_exhaustive: (),
         } = self
         else {
@@ -163,6 +358,113 @@ impl LocationOptions {
     }
 }
 
+/// How a request updates the browser history, as in the `push` and `replace`
+/// options of an `HX-Location` header.
+///
+/// Serialized as `"true"`, `"false"`, or the url, as htmx expects.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HistoryUpdate {
+    /// Uses the url of the request.
+    Enabled,
+    /// Leaves the history untouched.
+    Disabled,
+    /// Uses the given url.
+    Url(String),
+}
+
+impl HistoryUpdate {
+    /// Returns the value used by htmx.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Enabled => "true",
+            Self::Disabled => "false",
+            Self::Url(url) => url,
+        }
+    }
+
+    /// Returns the value used by htmx, with the url percent-encoded as the
+    /// path of the location.
+    fn encoded(&self) -> Cow<'_, str> {
+        match self {
+            Self::Url(url) => percent_encode(url),
+            _ => Cow::Borrowed(self.as_str()),
+        }
+    }
+}
+
+impl From<bool> for HistoryUpdate {
+    fn from(enabled: bool) -> Self {
+        if enabled {
+            Self::Enabled
+        } else {
+            Self::Disabled
+        }
+    }
+}
+
+impl From<&str> for HistoryUpdate {
+    fn from(url: &str) -> Self {
+        Self::Url(url.to_owned())
+    }
+}
+
+impl From<String> for HistoryUpdate {
+    fn from(url: String) -> Self {
+        Self::Url(url)
+    }
+}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(feature = "unstable", doc(cfg(feature = "serde")))]
+impl serde::Serialize for HistoryUpdate {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+/// Deserializes a boolean, `"true"`, `"false"`, or a url.
+#[cfg(feature = "serde")]
+#[cfg_attr(feature = "unstable", doc(cfg(feature = "serde")))]
+impl<'de> serde::Deserialize<'de> for HistoryUpdate {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct Visitor;
+
+        impl serde::de::Visitor<'_> for Visitor {
+            type Value = HistoryUpdate;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a boolean or a url")
+            }
+
+            fn visit_bool<E>(self, enabled: bool) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(enabled.into())
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(match value {
+                    "true" => HistoryUpdate::Enabled,
+                    "false" => HistoryUpdate::Disabled,
+                    url => url.into(),
+                })
+            }
+        }
+
+        deserializer.deserialize_any(Visitor)
+    }
+}
+
 #[cfg(test)]
 #[cfg(feature = "serde")]
 mod tests {
@@ -173,19 +475,105 @@ mod tests {
         use crate::SwapOption;
 
         let loc = HxLocation::from("/foo");
-        assert_eq!(loc.into_header_with_options().unwrap(), "/foo");
+        assert_eq!(loc.into_header_with_options(), "/foo");
 
         let loc = HxLocation::from_str_with_options(
             "/foo",
             LocationOptions {
                 event: Some("click".into()),
-                swap: Some(SwapOption::InnerHtml),
+                swap: Some(SwapOption::InnerHtml.into()),
                 ..Default::default()
             },
         );
         assert_eq!(
-            loc.into_header_with_options().unwrap(),
+            loc.into_header_with_options(),
             r#"{"path":"/foo","event":"click","swap":"innerHTML"}"#
         );
     }
+
+    #[test]
+    fn location_builder() {
+        use std::collections::HashMap;
+
+        #[derive(serde::Serialize)]
+        struct Filters {
+            page: u32,
+        }
+
+        let loc = HxLocation::from("/items")
+            .target("#main")
+            .swap(crate::SwapOption::OuterHtml)
+            .select("#list")
+            .push(false)
+            .replace("/items?page=2")
+            .values(Filters { page: 2 })
+            .unwrap()
+            .headers(HashMap::from([("X-Mode", "compact")]));
+        assert_eq!(
+            loc.into_header_with_options(),
+            r##"{"path":"/items","target":"#main","swap":"outerHTML","values":{"page":2},"headers":{"X-Mode":"compact"},"select":"#list","push":"false","replace":"/items?page=2"}"##
+        );
+
+        let loc = HxLocation::from("/").push(true);
+        assert_eq!(
+            loc.into_header_with_options(),
+            r#"{"path":"/","push":"true"}"#
+        );
+    }
+
+    #[test]
+    fn non_ascii_location() {
+        let loc = HxLocation::from("/projects/café");
+        assert_eq!(loc.into_header_with_options(), "/projects/caf%C3%A9");
+
+        let loc = HxLocation::from("/projects/café")
+            .target("#main")
+            .headers([("X-Name", "Zoë")]);
+        assert_eq!(
+            loc.into_header_with_options(),
+            r##"{"path":"/projects/caf%C3%A9","target":"#main","headers":{"X-Name":"Zo\u00eb"}}"##
+        );
+
+        let loc = HxLocation::from("/projects")
+            .push("/projects/café")
+            .replace(true);
+        assert_eq!(
+            loc.into_header_with_options(),
+            r#"{"path":"/projects","push":"/projects/caf%C3%A9","replace":"true"}"#
+        );
+    }
+
+    #[test]
+    fn test_deserialize_location() {
+        let loc = HxLocation::try_from(&HeaderValue::from_static("/foo")).unwrap();
+        assert_eq!(loc.uri, "/foo");
+        assert!(loc.options.is_default());
+
+        let loc = HxLocation::try_from(&HeaderValue::from_static(
+            r##"{"path":"/foo","target":"#main","swap":"outerHTML","values":{"a":1}}"##,
+        ))
+        .unwrap();
+        assert_eq!(loc.uri, "/foo");
+        assert_eq!(loc.options.target.as_deref(), Some("#main"));
+        assert_eq!(loc.options.swap, Some(crate::SwapOption::OuterHtml.into()));
+        assert_eq!(
+            loc.options.values.as_ref().map(RawJson::get),
+            Some(r#"{"a":1}"#)
+        );
+
+        let loc = HxLocation::try_from(&HeaderValue::from_static(
+            r##"{"path":"/foo","select":"#content","push":false,"replace":"/bar"}"##,
+        ))
+        .unwrap();
+        assert_eq!(loc.options.select.as_deref(), Some("#content"));
+        assert_eq!(loc.options.push, Some(HistoryUpdate::Disabled));
+        assert_eq!(loc.options.replace, Some(HistoryUpdate::Url("/bar".into())));
+
+        let err = HxLocation::try_from(&HeaderValue::from_static(r##"{"target":"#main"}"##));
+        assert!(matches!(err, Err(HxDecodeError::Json(_))));
+        let err = HxLocation::try_from(&HeaderValue::from_static(
+            r#"{"path":"/","swap":"innerHTML swap:soon"}"#,
+        ));
+        assert!(matches!(err, Err(HxDecodeError::Json(_))));
+    }
 }
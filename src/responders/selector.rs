@@ -0,0 +1,441 @@
+use std::{fmt, fmt::Write as _, str::FromStr};
+
+use crate::SelectorError;
+
+/// A CSS selector, or one of the extended targets of htmx, validated when it
+/// is built.
+///
+/// Converts into an [`HxRetarget`](crate::HxRetarget), and into an
+/// [`HxReselect`](crate::HxReselect) unless it is an extended target, which
+/// `hx-select` does not support. Elsewhere, it is passed with
+/// [`as_str`](Self::as_str).
+///
+/// Characters that are not ASCII are written as CSS escapes, so selectors can
+/// always be sent in a header.
+///
+/// ```rust
+/// use axum_htmx::{HxLocation, HxRetarget, Selector};
+///
+/// let user = Selector::id("user:42")?;
+/// assert_eq!(user.as_str(), r"#user\:42");
+///
+/// let retarget = HxRetarget::from(Selector::closest(Selector::class("card")?)?);
+/// assert_eq!(retarget.0, "closest .card");
+///
+/// let location = HxLocation::from("/users/42").target(user.as_str());
+/// # Ok::<(), axum_htmx::SelectorError>(())
+/// ```
+///
+/// See <https://htmx.org/attributes/hx-target/> for more information.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Selector {
+    value: String,
+    extended: bool,
+}
+
+impl Selector {
+    /// Selects the element with the given id, escaping it as needed.
+    pub fn id(id: impl AsRef<str>) -> Result<Self, SelectorError> {
+        Ok(Self::css_unchecked(format!(
+            "#{}",
+            escape_ident(id.as_ref())?
+        )))
+    }
+
+    /// Selects the elements with the given class, escaping it as needed.
+    pub fn class(class: impl AsRef<str>) -> Result<Self, SelectorError> {
+        Ok(Self::css_unchecked(format!(
+            ".{}",
+            escape_ident(class.as_ref())?
+        )))
+    }
+
+    /// Parses a CSS selector, such as `#list > li:first-child`.
+    ///
+    /// Fails if the selector is empty, contains control characters other than
+    /// whitespace, has unbalanced brackets, quotes, or escapes, or has a `#` or
+    /// `.` without a name. Whitespace is written as spaces. Use [`FromStr`] to
+    /// also parse the extended targets of htmx.
+    pub fn css(selector: impl AsRef<str>) -> Result<Self, SelectorError> {
+        parse_css(selector.as_ref()).map(Self::css_unchecked)
+    }
+
+    /// The element that triggered the request.
+    pub fn this() -> Self {
+        Self::extended("this".to_owned())
+    }
+
+    /// The closest ancestor of the triggering element, or the element itself,
+    /// matching `selector`.
+    ///
+    /// Fails if `selector` is an extended target.
+    pub fn closest(selector: Selector) -> Result<Self, SelectorError> {
+        selector
+            .into_css()
+            .map(|css| Self::extended(format!("closest {css}")))
+    }
+
+    /// The first child of the triggering element matching `selector`.
+    ///
+    /// Fails if `selector` is an extended target.
+    pub fn find(selector: Selector) -> Result<Self, SelectorError> {
+        selector
+            .into_css()
+            .map(|css| Self::extended(format!("find {css}")))
+    }
+
+    /// The first element after the triggering element, in document order,
+    /// matching `selector`.
+    ///
+    /// Fails if `selector` is an extended target.
+    pub fn next(selector: Selector) -> Result<Self, SelectorError> {
+        selector
+            .into_css()
+            .map(|css| Self::extended(format!("next {css}")))
+    }
+
+    /// The first element before the triggering element, in document order,
+    /// matching `selector`.
+    ///
+    /// Fails if `selector` is an extended target.
+    pub fn previous(selector: Selector) -> Result<Self, SelectorError> {
+        selector
+            .into_css()
+            .map(|css| Self::extended(format!("previous {css}")))
+    }
+
+    /// The next sibling of the triggering element.
+    pub fn next_sibling() -> Self {
+        Self::extended("next".to_owned())
+    }
+
+    /// The previous sibling of the triggering element.
+    pub fn previous_sibling() -> Self {
+        Self::extended("previous".to_owned())
+    }
+
+    /// The document.
+    pub fn document() -> Self {
+        Self::extended("document".to_owned())
+    }
+
+    /// The window.
+    pub fn window() -> Self {
+        Self::extended("window".to_owned())
+    }
+
+    /// Returns the selector, as written in htmx attributes and headers.
+    pub fn as_str(&self) -> &str {
+        &self.value
+    }
+
+    /// Returns `true` if the selector is one of the extended targets of htmx,
+    /// rather than a CSS selector.
+    pub fn is_extended(&self) -> bool {
+        self.extended
+    }
+
+    pub(crate) fn into_string(self) -> String {
+        self.value
+    }
+
+    fn css_unchecked(value: String) -> Self {
+        Self {
+            value,
+            extended: false,
+        }
+    }
+
+    fn extended(value: String) -> Self {
+        Self {
+            value,
+            extended: true,
+        }
+    }
+
+    pub(crate) fn into_css(self) -> Result<String, SelectorError> {
+        if self.extended {
+            return Err(SelectorError::Extended(self.value));
+        }
+
+        Ok(self.value)
+    }
+}
+
+/// Parses a CSS selector or an extended target of htmx, such as `this`,
+/// `closest .card`, or `next li`.
+///
+/// ```rust
+/// use axum_htmx::Selector;
+///
+/// let selector: Selector = "closest tr".parse()?;
+/// assert!(selector.is_extended());
+///
+/// assert!("[data-id='42'".parse::<Selector>().is_err());
+/// # Ok::<(), axum_htmx::SelectorError>(())
+/// ```
+impl FromStr for Selector {
+    type Err = SelectorError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+
+        match value {
+            "this" | "next" | "previous" | "document" | "window" => {
+                return Ok(Self::extended(value.to_owned()));
+            }
+            _ => {}
+        }
+
+        if let Some((keyword, selector)) = value.split_once(char::is_whitespace) {
+            if let "closest" | "find" | "next" | "previous" = keyword {
+                let css = parse_css(selector)?;
+                return Ok(Self::extended(format!("{keyword} {css}")));
+            }
+        }
+
+        Self::css(value)
+    }
+}
+
+impl fmt::Display for Selector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.value)
+    }
+}
+
+/// Escapes an id or a class name as an identifier, as `CSS.escape()` does.
+///
+/// Characters that are not ASCII are escaped too, unlike `CSS.escape()`.
+fn escape_ident(ident: &str) -> Result<String, SelectorError> {
+    if ident.is_empty() {
+        return Err(SelectorError::Empty);
+    }
+
+    let starts_with_dash = ident.starts_with('-');
+    let mut escaped = String::with_capacity(ident.len() + 4);
+
+    for (i, c) in ident.chars().enumerate() {
+        match c {
+            '\0' => escaped.push_str("\\fffd "),
+            '0'..='9' if i == 0 || (i == 1 && starts_with_dash) => push_hex(&mut escaped, c),
+            '-' if ident.len() == 1 => escaped.push_str("\\-"),
+            c if c.is_ascii_alphanumeric() || c == '-' || c == '_' => escaped.push(c),
+            c if c.is_ascii_control() || !c.is_ascii() => push_hex(&mut escaped, c),
+            c => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+        }
+    }
+
+    Ok(escaped)
+}
+
+/// Checks a CSS selector, escapes the characters that are not ASCII, and
+/// replaces whitespace with spaces.
+fn parse_css(selector: &str) -> Result<String, SelectorError> {
+    let selector = selector.trim();
+    if selector.is_empty() {
+        return Err(SelectorError::Empty);
+    }
+
+    let unbalanced = || SelectorError::Unbalanced(selector.to_owned());
+    let mut parsed = String::with_capacity(selector.len());
+    let mut closing = Vec::new();
+    let mut quote = None;
+    let mut chars = selector.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            // Tabs, line breaks, and form feeds are whitespace in CSS, but
+            // cannot all be sent in a header.
+            '\t' | '\n' | '\r' | '\x0c' if quote.is_none() => {
+                parsed.push(' ');
+                continue;
+            }
+            c if c.is_control() => return Err(SelectorError::InvalidCharacter(c)),
+            '\\' => {
+                parsed.push('\\');
+                match chars.next() {
+                    Some(c) if c.is_control() => return Err(SelectorError::InvalidCharacter(c)),
+                    // `\é` and `\e9 ` are the same escape.
+                    Some(c) if !c.is_ascii() => {
+                        let _ = write!(parsed, "{:x} ", c as u32);
+                    }
+                    Some(c) => parsed.push(c),
+                    None => return Err(unbalanced()),
+                }
+                continue;
+            }
+            '"' | '\'' if quote == Some(c) => quote = None,
+            '"' | '\'' if quote.is_none() => quote = Some(c),
+            '#' | '.' if quote.is_none() && !closing.contains(&']') => {
+                let starts_name = |c: char| {
+                    c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '\\') || !c.is_ascii()
+                };
+                if !chars.clone().next().is_some_and(starts_name) {
+                    return Err(SelectorError::MissingName(selector.to_owned()));
+                }
+            }
+            '(' if quote.is_none() => closing.push(')'),
+            '[' if quote.is_none() => closing.push(']'),
+            ')' | ']' if quote.is_none() => match closing.pop() {
+                Some(expected) if expected == c => {}
+                _ => return Err(unbalanced()),
+            },
+            _ => {}
+        }
+
+        if c.is_ascii() {
+            parsed.push(c);
+        } else {
+            push_hex(&mut parsed, c);
+        }
+    }
+
+    if quote.is_some() || !closing.is_empty() {
+        return Err(unbalanced());
+    }
+
+    Ok(parsed)
+}
+
+/// Writes `c` as a CSS hex escape, followed by the space ending it.
+fn push_hex(out: &mut String, c: char) {
+    let _ = write!(out, "\\{:x} ", c as u32);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_identifiers() {
+        let cases = [
+            ("main", "main"),
+            ("user:42", r"user\:42"),
+            ("42", r"\34 2"),
+            ("-4", r"-\34 "),
+            ("-", r"\-"),
+            ("a.b c", r"a\.b\ c"),
+            ("café", r"caf\e9 "),
+            ("tab\tbed", r"tab\9 bed"),
+        ];
+        for (ident, escaped) in cases {
+            assert_eq!(Selector::id(ident).unwrap().as_str(), format!("#{escaped}"));
+            assert_eq!(
+                Selector::class(ident).unwrap().as_str(),
+                format!(".{escaped}")
+            );
+        }
+
+        assert_eq!(Selector::id(""), Err(SelectorError::Empty));
+    }
+
+    #[test]
+    fn css_selectors() {
+        let selector = Selector::css(r#"#list > li[data-name="a)b"]:not(.done)"#).unwrap();
+        assert_eq!(
+            selector.as_str(),
+            r#"#list > li[data-name="a)b"]:not(.done)"#
+        );
+        assert!(!selector.is_extended());
+
+        assert_eq!(
+            Selector::css(r#"[title="Café"]"#).unwrap().as_str(),
+            r#"[title="Caf\e9 "]"#
+        );
+        assert_eq!(Selector::css(r"#a\é").unwrap().as_str(), r"#a\e9 ");
+
+        assert_eq!(Selector::css("  "), Err(SelectorError::Empty));
+        assert_eq!(
+            Selector::css("li\0"),
+            Err(SelectorError::InvalidCharacter('\0'))
+        );
+        for selector in ["li:not(.done", "li]", "[title='a]", r"#a\", "a(]"] {
+            assert_eq!(
+                Selector::css(selector),
+                Err(SelectorError::Unbalanced(selector.to_owned()))
+            );
+        }
+
+        assert_eq!(
+            Selector::css("#list >\n\tli,\r\n.item").unwrap().as_str(),
+            "#list >  li,  .item"
+        );
+        assert_eq!(
+            Selector::css("[title='a\nb']"),
+            Err(SelectorError::InvalidCharacter('\n'))
+        );
+
+        for selector in ["#", "..", "# a", "li.", "a:not(.)"] {
+            assert_eq!(
+                Selector::css(selector),
+                Err(SelectorError::MissingName(selector.to_owned()))
+            );
+        }
+        for selector in ["a[href='#']", "a[href=\"a.html\"]", r"#\31 0", ".-a", "#é"] {
+            assert!(Selector::css(selector).is_ok(), "{selector}");
+        }
+    }
+
+    #[test]
+    fn extended_targets() {
+        let card = Selector::class("card").unwrap();
+        assert_eq!(
+            Selector::closest(card.clone()).unwrap().as_str(),
+            "closest .card"
+        );
+        assert_eq!(Selector::next(card.clone()).unwrap().as_str(), "next .card");
+        assert_eq!(
+            Selector::previous(card.clone()).unwrap().as_str(),
+            "previous .card"
+        );
+        assert_eq!(Selector::find(card).unwrap().as_str(), "find .card");
+        assert_eq!(
+            Selector::closest(Selector::this()),
+            Err(SelectorError::Extended("this".to_owned()))
+        );
+
+        for value in ["this", "next", "previous", "document", "window"] {
+            let selector: Selector = value.parse().unwrap();
+            assert!(selector.is_extended());
+            assert_eq!(selector.as_str(), value);
+        }
+        assert_eq!(Selector::this().as_str(), "this");
+        assert_eq!(Selector::next_sibling().as_str(), "next");
+        assert_eq!(Selector::previous_sibling().as_str(), "previous");
+        assert_eq!(Selector::window().to_string(), "window");
+
+        let selector: Selector = " next\tli.item ".parse().unwrap();
+        assert_eq!(selector.as_str(), "next li.item");
+
+        let selector: Selector = " next  li.item ".parse().unwrap();
+        assert_eq!(selector.as_str(), "next li.item");
+        assert!(selector.is_extended());
+
+        let selector: Selector = "#main".parse().unwrap();
+        assert!(!selector.is_extended());
+
+        assert_eq!(
+            "find [a".parse::<Selector>(),
+            Err(SelectorError::Unbalanced("[a".to_owned()))
+        );
+    }
+
+    #[test]
+    fn responder_conversions() {
+        use crate::{HxReselect, HxRetarget};
+
+        let closest: Selector = "closest tr".parse().unwrap();
+        assert_eq!(HxRetarget::from(closest.clone()).0, "closest tr");
+        assert!(matches!(
+            HxReselect::try_from(closest),
+            Err(SelectorError::Extended(value)) if value == "closest tr"
+        ));
+
+        let list = Selector::id("list").unwrap();
+        assert_eq!(HxReselect::try_from(list).unwrap().0, "#list");
+    }
+}
@@ -0,0 +1,547 @@
+use std::{fmt, str::FromStr, time::Duration};
+
+use http::HeaderValue;
+
+use crate::{
+    HxDecodeError, SwapOption, SwapParseError, encoding::css_escape, responders::header_str,
+};
+
+/// A swap style with the modifiers of the `hx-swap` attribute.
+///
+/// Accepted by [`HxReswap`](crate::HxReswap) and
+/// [`LocationOptions::swap`](crate::LocationOptions::swap), and converted from
+/// a bare [`SwapOption`] where those are expected.
+///
+/// ```rust
+/// use std::time::Duration;
+///
+/// use axum_htmx::{Scroll, ScrollPosition, Swap, SwapOption};
+///
+/// let swap = Swap::new(SwapOption::InnerHtml)
+///     .swap(Duration::from_secs(1))
+///     .settle(Duration::from_millis(200))
+///     .scroll(ScrollPosition::Top)
+///     .show(Scroll::element("#el", ScrollPosition::Top))
+///     .transition(true);
+///
+/// assert_eq!(
+///     swap.to_string(),
+///     "innerHTML swap:1s settle:200ms scroll:top show:#el:top transition:true"
+/// );
+/// ```
+///
+/// See <https://htmx.org/attributes/hx-swap/> for more information.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Swap {
+    style: SwapOption,
+    swap: Option<Duration>,
+    settle: Option<Duration>,
+    scroll: Option<Scroll>,
+    show: Option<Scroll>,
+    focus_scroll: Option<bool>,
+    transition: Option<bool>,
+    ignore_title: Option<bool>,
+}
+
+impl Swap {
+    /// Creates a swap with the given style and no modifiers.
+    pub fn new(style: SwapOption) -> Self {
+        Self {
+            style,
+            swap: None,
+            settle: None,
+            scroll: None,
+            show: None,
+            focus_scroll: None,
+            transition: None,
+            ignore_title: None,
+        }
+    }
+
+    /// Returns the swap style.
+    pub fn style(&self) -> &SwapOption {
+        &self.style
+    }
+
+    /// Delays the swap of the new content (`swap:`).
+    pub fn swap(mut self, delay: Duration) -> Self {
+        self.swap = Some(delay);
+        self
+    }
+
+    /// Delays the settle step after the swap (`settle:`).
+    pub fn settle(mut self, delay: Duration) -> Self {
+        self.settle = Some(delay);
+        self
+    }
+
+    /// Scrolls the target, or another element, after the swap (`scroll:`).
+    pub fn scroll(mut self, scroll: impl Into<Scroll>) -> Self {
+        self.scroll = Some(scroll.into());
+        self
+    }
+
+    /// Scrolls the target, or another element, into view after the swap
+    /// (`show:`).
+    pub fn show(mut self, show: impl Into<Scroll>) -> Self {
+        self.show = Some(show.into());
+        self
+    }
+
+    /// Whether the focused element should be scrolled into view
+    /// (`focus-scroll:`).
+    pub fn focus_scroll(mut self, focus_scroll: bool) -> Self {
+        self.focus_scroll = Some(focus_scroll);
+        self
+    }
+
+    /// Whether the swap should use the View Transition API (`transition:`).
+    pub fn transition(mut self, transition: bool) -> Self {
+        self.transition = Some(transition);
+        self
+    }
+
+    /// Whether a `<title>` in the response should be ignored
+    /// (`ignoreTitle:`).
+    pub fn ignore_title(mut self, ignore_title: bool) -> Self {
+        self.ignore_title = Some(ignore_title);
+        self
+    }
+}
+
+/// Parses the value of an `hx-swap` attribute or `HX-Reswap` header.
+///
+/// As in htmx, the first part is the style unless it is a modifier, and a
+/// missing style defaults to `innerHTML`, the htmx default.
+///
+/// ```rust
+/// use axum_htmx::Swap;
+///
+/// let swap: Swap = "outerHTML settle:1s show:top".parse().unwrap();
+/// assert_eq!(swap.to_string(), "outerHTML settle:1s show:top");
+///
+/// let err = "outerHTML settle:soon".parse::<Swap>().unwrap_err();
+/// assert_eq!(
+///     err.to_string(),
+///     "Invalid value `soon` for swap modifier `settle`, expected a time interval such as `1s` or `200ms`"
+/// );
+/// ```
+impl FromStr for Swap {
+    type Err = SwapParseError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut parts = value.split_whitespace().peekable();
+
+        let style = match parts.next_if(|part| !is_modifier(part)) {
+            Some(style) => style.parse()?,
+            None if value.trim().is_empty() => return Err(SwapParseError::Empty),
+            None => SwapOption::InnerHtml,
+        };
+
+        let mut swap = Self::new(style);
+        for part in parts {
+            let (name, value) = part
+                .split_once(':')
+                .ok_or_else(|| SwapParseError::UnknownModifier(part.to_string()))?;
+
+            match name {
+                "swap" => swap.swap = Some(parse_duration("swap", value)?),
+                "settle" => swap.settle = Some(parse_duration("settle", value)?),
+                "scroll" => swap.scroll = Some(Scroll::parse("scroll", value)?),
+                "show" => swap.show = Some(Scroll::parse("show", value)?),
+                "focus-scroll" => swap.focus_scroll = Some(parse_bool("focus-scroll", value)?),
+                "transition" => swap.transition = Some(parse_bool("transition", value)?),
+                "ignoreTitle" => swap.ignore_title = Some(parse_bool("ignoreTitle", value)?),
+                _ => return Err(SwapParseError::UnknownModifier(name.to_string())),
+            }
+        }
+
+        Ok(swap)
+    }
+}
+
+impl TryFrom<&HeaderValue> for Swap {
+    type Error = HxDecodeError;
+
+    fn try_from(value: &HeaderValue) -> Result<Self, Self::Error> {
+        Ok(header_str(value)?.parse()?)
+    }
+}
+
+impl From<SwapOption> for Swap {
+    fn from(style: SwapOption) -> Self {
+        Self::new(style)
+    }
+}
+
+impl fmt::Display for Swap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.style.as_str())?;
+
+        if let Some(delay) = self.swap {
+            write!(f, " swap:{}", DisplayDuration(delay))?;
+        }
+        if let Some(delay) = self.settle {
+            write!(f, " settle:{}", DisplayDuration(delay))?;
+        }
+        if let Some(scroll) = &self.scroll {
+            write!(f, " scroll:{scroll}")?;
+        }
+        if let Some(show) = &self.show {
+            write!(f, " show:{show}")?;
+        }
+        if let Some(focus_scroll) = self.focus_scroll {
+            write!(f, " focus-scroll:{focus_scroll}")?;
+        }
+        if let Some(transition) = self.transition {
+            write!(f, " transition:{transition}")?;
+        }
+        if let Some(ignore_title) = self.ignore_title {
+            write!(f, " ignoreTitle:{ignore_title}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Characters that cannot be sent in a header, e.g. in the selectors of the
+/// `scroll` and `show` modifiers, are written as CSS escapes.
+impl From<&Swap> for HeaderValue {
+    fn from(value: &Swap) -> Self {
+        HeaderValue::try_from(css_escape(&value.to_string()).into_owned())
+            .expect("escaped swap values are visible ASCII")
+    }
+}
+
+#[cfg(feature = "serde")]
+impl ::serde::Serialize for Swap {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ::serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> ::serde::Deserialize<'de> for Swap {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: ::serde::Deserializer<'de>,
+    {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(::serde::de::Error::custom)
+    }
+}
+
+/// An edge of an element to scroll to, used by [`Scroll`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScrollPosition {
+    /// The top of the element.
+    Top,
+    /// The bottom of the element.
+    Bottom,
+}
+
+/// The value of the `scroll` and `show` modifiers of [`Swap`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Scroll {
+    /// Scrolls the target element.
+    Target(ScrollPosition),
+    /// Scrolls the element matching a CSS selector, or `window`.
+    Element(String, ScrollPosition),
+    /// Disables scrolling, e.g. `show:none`.
+    None,
+}
+
+impl Scroll {
+    /// Scrolls the element matching `selector`, or `window`.
+    pub fn element(selector: impl Into<String>, position: ScrollPosition) -> Self {
+        Self::Element(selector.into(), position)
+    }
+
+    fn parse(modifier: &'static str, value: &str) -> Result<Self, SwapParseError> {
+        if value == "none" {
+            return Ok(Self::None);
+        }
+
+        let invalid = || SwapParseError::InvalidModifierValue {
+            modifier,
+            value: value.to_string(),
+            expected: "`none`, `top`, `bottom`, or a selector followed by `:top` or `:bottom`",
+        };
+
+        // Selectors may contain colons, the position is always the last part.
+        let (selector, position) = match value.rsplit_once(':') {
+            Some((selector, position)) => (Some(selector), position),
+            None => (None, value),
+        };
+        let position = match position {
+            "top" => ScrollPosition::Top,
+            "bottom" => ScrollPosition::Bottom,
+            _ => return Err(invalid()),
+        };
+
+        match selector {
+            Some("") => Err(invalid()),
+            Some(selector) => Ok(Self::element(selector, position)),
+            None => Ok(Self::Target(position)),
+        }
+    }
+}
+
+impl From<ScrollPosition> for Scroll {
+    fn from(position: ScrollPosition) -> Self {
+        Self::Target(position)
+    }
+}
+
+impl fmt::Display for Scroll {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let position = |position| match position {
+            ScrollPosition::Top => "top",
+            ScrollPosition::Bottom => "bottom",
+        };
+
+        match self {
+            Scroll::Target(pos) => f.write_str(position(*pos)),
+            Scroll::Element(selector, pos) => write!(f, "{selector}:{}", position(*pos)),
+            Scroll::None => f.write_str("none"),
+        }
+    }
+}
+
+/// Formats a duration as htmx time interval, in whole seconds when possible.
+///
+/// Other durations are written in milliseconds, with a decimal part for
+/// sub-millisecond precision, so that no precision is lost.
+struct DisplayDuration(Duration);
+
+impl fmt::Display for DisplayDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let nanos = self.0.as_nanos();
+        if nanos != 0 && nanos.is_multiple_of(1_000_000_000) {
+            return write!(f, "{}s", nanos / 1_000_000_000);
+        }
+
+        let (millis, fraction) = (nanos / 1_000_000, nanos % 1_000_000);
+        if fraction == 0 {
+            write!(f, "{millis}ms")
+        } else {
+            let fraction = format!("{fraction:06}");
+            write!(f, "{millis}.{}ms", fraction.trim_end_matches('0'))
+        }
+    }
+}
+
+/// Parses an htmx time interval such as `1s`, `0.5s`, `200ms` or `200`.
+///
+/// Decimal values are accepted as htmx does, up to nanosecond precision.
+fn parse_duration(modifier: &'static str, value: &str) -> Result<Duration, SwapParseError> {
+    let invalid = || SwapParseError::InvalidModifierValue {
+        modifier,
+        value: value.to_string(),
+        expected: "a time interval such as `1s` or `200ms`",
+    };
+
+    let (number, unit) = match value.find(|c: char| !c.is_ascii_digit() && c != '.') {
+        Some(i) => value.split_at(i),
+        None => (value, "ms"),
+    };
+    let nanos_per_unit: u128 = match unit {
+        "ms" => 1_000_000,
+        "s" => 1_000_000_000,
+        "m" => 60_000_000_000,
+        _ => return Err(invalid()),
+    };
+
+    let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+    if (whole.is_empty() && fraction.is_empty()) || fraction.contains('.') {
+        return Err(invalid());
+    }
+    let whole = match whole {
+        "" => 0,
+        whole => whole.parse::<u128>().map_err(|_| invalid())?,
+    };
+    // Digits beyond nanosecond precision are ignored.
+    let fraction = fraction.get(..fraction.len().min(12)).unwrap_or(fraction);
+    let fraction_nanos = match fraction {
+        "" => 0,
+        digits => {
+            let scale = 10u128.pow(digits.len() as u32);
+            digits.parse::<u128>().map_err(|_| invalid())? * nanos_per_unit / scale
+        }
+    };
+
+    let nanos = whole
+        .checked_mul(nanos_per_unit)
+        .and_then(|nanos| nanos.checked_add(fraction_nanos))
+        .ok_or_else(invalid)?;
+    let secs = u64::try_from(nanos / 1_000_000_000).map_err(|_| invalid())?;
+
+    Ok(Duration::new(secs, (nanos % 1_000_000_000) as u32))
+}
+
+fn is_modifier(part: &str) -> bool {
+    const MODIFIERS: [&str; 7] = [
+        "swap:",
+        "settle:",
+        "scroll:",
+        "show:",
+        "focus-scroll:",
+        "transition:",
+        "ignoreTitle:",
+    ];
+
+    MODIFIERS.iter().any(|modifier| part.starts_with(modifier))
+}
+
+fn parse_bool(modifier: &'static str, value: &str) -> Result<bool, SwapParseError> {
+    match value {
+        "true" => Ok(true),
+        "false" => Ok(false),
+        _ => Err(SwapParseError::InvalidModifierValue {
+            modifier,
+            value: value.to_string(),
+            expected: "`true` or `false`",
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn swap_modifiers() {
+        let swap = Swap::new(SwapOption::OuterHtml)
+            .swap(Duration::from_millis(1500))
+            .settle(Duration::ZERO)
+            .scroll(Scroll::element("window", ScrollPosition::Bottom))
+            .show(Scroll::None)
+            .focus_scroll(false)
+            .ignore_title(true);
+        let value = "outerHTML swap:1500ms settle:0ms scroll:window:bottom show:none \
+                     focus-scroll:false ignoreTitle:true";
+
+        assert_eq!(swap.to_string(), value);
+        assert_eq!(value.parse::<Swap>().unwrap(), swap);
+        assert_eq!(Swap::from(SwapOption::Delete).to_string(), "delete");
+
+        let swap = Swap::new(SwapOption::InnerHtml).swap(Duration::from_micros(1250));
+        assert_eq!(swap.to_string(), "innerHTML swap:1.25ms");
+        assert_eq!(swap.to_string().parse::<Swap>().unwrap(), swap);
+
+        let swap =
+            Swap::new(SwapOption::InnerHtml).show(Scroll::element("#café", ScrollPosition::Top));
+        assert_eq!(HeaderValue::from(&swap), "innerHTML show:#caf\\0000e9:top");
+    }
+
+    #[test]
+    fn parse_durations() {
+        let durations = [
+            ("200", Duration::from_millis(200)),
+            ("200ms", Duration::from_millis(200)),
+            ("0.5s", Duration::from_millis(500)),
+            (".25s", Duration::from_millis(250)),
+            ("1.", Duration::from_millis(1)),
+            ("1.5m", Duration::from_secs(90)),
+            ("0.0005ms", Duration::from_nanos(500)),
+        ];
+        for (value, duration) in durations {
+            assert_eq!(parse_duration("swap", value), Ok(duration), "{value}");
+        }
+
+        for value in ["", ".", "1.2.3s", "-1s", "1e3ms", "1h", "s"] {
+            assert!(parse_duration("swap", value).is_err(), "{value}");
+        }
+    }
+
+    #[test]
+    fn parse_swap() {
+        let swap = "  show:a:hover:top  swap:1s ".parse::<Swap>().unwrap();
+        assert_eq!(
+            swap,
+            Swap::new(SwapOption::InnerHtml)
+                .show(Scroll::element("a:hover", ScrollPosition::Top))
+                .swap(Duration::from_secs(1))
+        );
+
+        let swap = "morph:outerHTML transition:true".parse::<Swap>().unwrap();
+        assert_eq!(swap.style(), &SwapOption::MorphOuterHtml);
+        let swap = "morph settle:1s".parse::<Swap>().unwrap();
+        assert_eq!(swap.style(), &SwapOption::Custom("morph".into()));
+        assert_eq!(swap.to_string(), "morph settle:1s");
+        assert_eq!("  ".parse::<Swap>(), Err(SwapParseError::Empty));
+        assert_eq!(
+            "innerHTML outerHTML".parse::<Swap>(),
+            Err(SwapParseError::UnknownModifier("outerHTML".into()))
+        );
+        assert_eq!(
+            "innerHTML delay:1s".parse::<Swap>(),
+            Err(SwapParseError::UnknownModifier("delay".into()))
+        );
+        assert!(matches!(
+            "innerHTML scroll:left".parse::<Swap>(),
+            Err(SwapParseError::InvalidModifierValue {
+                modifier: "scroll",
+                ..
+            })
+        ));
+        assert!(matches!(
+            "innerHTML transition:yes".parse::<Swap>(),
+            Err(SwapParseError::InvalidModifierValue {
+                modifier: "transition",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn swap_option_names() {
+        let options = [
+            (SwapOption::TextContent, "textContent"),
+            (SwapOption::MorphInnerHtml, "morph:innerHTML"),
+            (SwapOption::Custom("multi:#a,#b".into()), "multi:#a,#b"),
+        ];
+
+        for (option, name) in options {
+            assert_eq!(HeaderValue::from(option.clone()), name);
+            #[cfg(feature = "serde")]
+            assert_eq!(serde_json::to_value(&option).unwrap(), name);
+            assert_eq!(name.parse(), Ok(option));
+        }
+
+        assert_eq!(
+            SwapOption::custom("morph settle:1s"),
+            Err(SwapParseError::NotASingleStyle("morph settle:1s".into()))
+        );
+        assert_eq!(
+            SwapOption::custom("morphé"),
+            Err(SwapParseError::InvalidStyle("morphé".into()))
+        );
+        assert_eq!(
+            HeaderValue::from(SwapOption::Custom("morph\x01".into())),
+            "morph\\000001"
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserialize_swap() {
+        let swap: Swap = serde_json::from_str(r#""beforeend scroll:bottom""#).unwrap();
+        assert_eq!(
+            swap,
+            Swap::new(SwapOption::BeforeEnd).scroll(ScrollPosition::Bottom)
+        );
+
+        let option: SwapOption = serde_json::from_str(r#""textContent""#).unwrap();
+        assert_eq!(option, SwapOption::TextContent);
+
+        let err = serde_json::from_str::<Swap>(r#""innerHTML settle:1h""#).unwrap_err();
+        assert!(err.to_string().starts_with(
+            "Invalid value `1h` for swap modifier `settle`, expected a time interval"
+        ));
+    }
+}
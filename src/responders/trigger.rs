@@ -1,16 +1,34 @@
+use std::convert::Infallible;
+
 use axum_core::response::{IntoResponseParts, ResponseParts};
 
-use crate::{HxError, headers};
+#[cfg(feature = "serde")]
+use serde_json::value::RawValue;
+
+use crate::{
+    HxDecodeError,
+    encoding::ascii_json,
+    headers,
+    json::{self, JsonValue, ObjectWriter, RawJson},
+};
 
 /// Represents a client-side event carrying optional data.
+///
+/// The data is kept as serialized JSON, so that it is written to the header
+/// as-is.
+///
+/// By default, htmx dispatches the event on the element that triggered the
+/// request. With a [`target`](Self::with_target), the event is dispatched on
+/// the element matching the selector instead.
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct HxEvent {
     pub name: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[cfg(feature = "serde")]
-    #[cfg_attr(feature = "unstable", doc(cfg(feature = "serde")))]
-    pub data: Option<serde_json::Value>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub data: Option<RawJson>,
+    /// A CSS selector of the element the event is dispatched on.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub target: Option<String>,
 }
 
 impl HxEvent {
@@ -18,8 +36,18 @@ impl HxEvent {
     pub fn new(name: impl AsRef<str>) -> Self {
         Self {
             name: name.as_ref().to_owned(),
-            #[cfg(feature = "serde")]
             data: None,
+            target: None,
+        }
+    }
+
+    /// Creates new event with data built from strings, numbers, booleans,
+    /// and maps, without the `serde` feature.
+    pub fn new_with_json(name: impl AsRef<str>, data: impl Into<JsonValue>) -> Self {
+        Self {
+            name: name.as_ref().to_owned(),
+            data: Some(data.into().into()),
+            target: None,
         }
     }
 
@@ -30,55 +58,97 @@ impl HxEvent {
         name: impl AsRef<str>,
         data: T,
     ) -> Result<Self, serde_json::Error> {
-        let data = serde_json::to_value(data)?;
+        let data = serde_json::value::to_raw_value(&data)?;
 
-        Ok(Self {
-            name: name.as_ref().to_owned(),
-            #[cfg(feature = "serde")]
-            data: Some(data),
-        })
+        Ok(Self::new_with_raw_data(name, data))
     }
-}
 
-impl<N: AsRef<str>> From<N> for HxEvent {
-    fn from(name: N) -> Self {
+    /// Creates new event with already serialized JSON data, which is written
+    /// to the header without being parsed again.
+    #[cfg(feature = "serde")]
+    #[cfg_attr(feature = "unstable", doc(cfg(feature = "serde")))]
+    pub fn new_with_raw_data(name: impl AsRef<str>, data: Box<RawValue>) -> Self {
         Self {
             name: name.as_ref().to_owned(),
-            #[cfg(feature = "serde")]
-            data: None,
+            data: Some(data.into()),
+            target: None,
         }
     }
-}
-
-#[cfg(not(feature = "serde"))]
-fn events_to_header_value(events: Vec<HxEvent>) -> Result<http::HeaderValue, HxError> {
-    let header = events
-        .into_iter()
-        .map(|HxEvent { name }| name)
-        .collect::<Vec<_>>()
-        .join(", ");
 
-    http::HeaderValue::from_str(&header).map_err(Into::into)
-}
+    /// Dispatches the event on the element matching `selector`, instead of
+    /// the element that triggered the request.
+    ///
+    /// The target is added to object data, other data is wrapped in an object
+    /// as `{"value": data, "target": selector}`, as htmx does when dispatching
+    /// events. Object data that already has a `target` key is wrapped as well,
+    /// so that its own `target` is kept rather than written twice.
+    ///
+    /// ```rust
+    /// use axum_htmx::HxEvent;
+    ///
+    /// let event = HxEvent::new("refresh").with_target("#cart-widget");
+    /// ```
+    pub fn with_target(mut self, selector: impl Into<String>) -> Self {
+        self.target = Some(selector.into());
+        self
+    }
 
-#[cfg(feature = "serde")]
-fn events_to_header_value(events: Vec<HxEvent>) -> Result<http::HeaderValue, HxError> {
-    use std::collections::HashMap;
+    /// Returns the detail of the event in a JSON encoded `HX-Trigger*` header.
+    fn detail(&self) -> String {
+        let data = self.data.as_ref().map(|data| data.get().trim());
+        let Some(target) = &self.target else {
+            return data.unwrap_or("null").to_owned();
+        };
+
+        let mut detail = String::new();
+        match data {
+            // Add the target to the object, without parsing the data.
+            Some(data) if data.starts_with('{') && !json::has_key(data, "target") => {
+                let fields = data[1..data.len() - 1].trim();
+                detail.push('{');
+                if !fields.is_empty() {
+                    detail.push_str(fields);
+                    detail.push(',');
+                }
+            }
+            Some(data) => {
+                detail.push_str(r#"{"value":"#);
+                detail.push_str(data);
+                detail.push(',');
+            }
+            None => detail.push('{'),
+        }
+        detail.push_str(r#""target":"#);
+        let _ = json::write_str(&mut detail, target);
+        detail.push('}');
 
-    use http::HeaderValue;
-    use serde_json::Value;
+        detail
+    }
+}
 
-    let with_data = events.iter().any(|e| e.data.is_some());
+impl<N: AsRef<str>> From<N> for HxEvent {
+    fn from(name: N) -> Self {
+        Self::new(name)
+    }
+}
 
-    let header_value = if with_data {
-        // at least one event contains data so the header_value needs to be json
-        // encoded.
-        let header_value = events
-            .into_iter()
-            .map(|e| (e.name, e.data.unwrap_or_default()))
-            .collect::<HashMap<String, Value>>();
+pub(crate) fn events_to_header_value(events: Vec<HxEvent>) -> http::HeaderValue {
+    let as_json = events.iter().any(|e| {
+        e.data.is_some()
+            || e.target.is_some()
+            || !e.name.bytes().all(|b| b.is_ascii_graphic() && b != b',')
+    });
+
+    let header_value = if as_json {
+        // at least one event contains data, or a name that cannot be listed,
+        // so the header_value needs to be json encoded, keeping the events in
+        // order.
+        let mut object = ObjectWriter::new();
+        for event in &events {
+            object.raw(&event.name, &event.detail());
+        }
 
-        serde_json::to_string(&header_value)?
+        ascii_json(&object.finish()).into_owned()
     } else {
         // no event contains data, the event names can be put in the header
         // value separated by a comma.
@@ -89,7 +159,62 @@ fn events_to_header_value(events: Vec<HxEvent>) -> Result<http::HeaderValue, HxE
             .unwrap_or_default()
     };
 
-    HeaderValue::from_maybe_shared(header_value).map_err(HxError::from)
+    http::HeaderValue::try_from(header_value)
+        .expect("listed event names and escaped JSON are visible ASCII")
+}
+
+/// Decodes the value of an `HX-Trigger*` header, either a comma separated list
+/// of event names or a JSON object mapping event names to their data.
+pub(crate) fn events_from_header_value(
+    value: &http::HeaderValue,
+) -> Result<Vec<HxEvent>, HxDecodeError> {
+    let value = crate::responders::header_str(value)?.trim();
+
+    if value.starts_with('{') {
+        let fields = json::object_fields(value).ok_or(HxDecodeError::InvalidJson)?;
+
+        // The target is not split out of the detail, as a target set with
+        // `HxEvent::with_target` cannot be told apart from a `target` key of
+        // the data. `HxEvent::new` encodes events without data as `null`.
+        return Ok(fields
+            .into_iter()
+            .map(|(name, detail)| HxEvent {
+                name,
+                data: (detail != "null").then(|| RawJson::from_trusted(detail.to_owned())),
+                target: None,
+            })
+            .collect());
+    }
+
+    Ok(value
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(HxEvent::new)
+        .collect())
+}
+
+/// Merges `events` into the `merged` events of an existing header.
+///
+/// Events keep the position of their first occurrence. When an event name is
+/// repeated, the data and target of the later event win, unless they are
+/// missing, in which case the existing ones are kept.
+fn merge_events(mut merged: Vec<HxEvent>, events: Vec<HxEvent>) -> Vec<HxEvent> {
+    for event in events {
+        match merged.iter_mut().find(|e| e.name == event.name) {
+            Some(existing) => {
+                if event.data.is_some() {
+                    existing.data = event.data;
+                }
+                if event.target.is_some() {
+                    existing.target = event.target;
+                }
+            }
+            None => merged.push(event),
+        }
+    }
+
+    merged
 }
 
 /// Describes when should event be triggered.
@@ -101,17 +226,49 @@ pub enum TriggerMode {
     AfterSwap,
 }
 
+impl TriggerMode {
+    /// Returns the name of the response header used for this mode.
+    pub fn header_name(self) -> http::HeaderName {
+        match self {
+            Self::Normal => headers::HX_TRIGGER,
+            Self::AfterSettle => headers::HX_TRIGGER_AFTER_SETTLE,
+            Self::AfterSwap => headers::HX_TRIGGER_AFTER_SWAP,
+        }
+    }
+
+    /// Returns the mode corresponding to an `HX-Trigger*` response header
+    /// name, if any.
+    pub fn from_header_name(name: &http::HeaderName) -> Option<Self> {
+        if name == headers::HX_TRIGGER {
+            Some(Self::Normal)
+        } else if name == headers::HX_TRIGGER_AFTER_SETTLE {
+            Some(Self::AfterSettle)
+        } else if name == headers::HX_TRIGGER_AFTER_SWAP {
+            Some(Self::AfterSwap)
+        } else {
+            None
+        }
+    }
+}
+
 /// The `HX-Trigger*` header.
 ///
 /// Allows you to trigger client-side events. Corresponds to `HX-Trigger`,
 /// `HX-Trigger-After-Settle` and `HX-Trigger-After-Swap` headers. To change
 /// when events trigger use appropriate `mode`.
 ///
-/// Will fail if the supplied events contain or produce characters that are not
-/// visible ASCII (32-127) when serializing to JSON.
+/// Characters that are not ASCII are escaped as `\uXXXX` in the JSON value,
+/// so events may carry any data and names.
 ///
 /// See <https://htmx.org/headers/hx-trigger/> for more information.
 ///
+/// If the response already has a header for the same mode, e.g. from another
+/// responder or a middleware, its events are decoded and merged with the new
+/// ones instead of being overwritten. Events keep the position of their first
+/// occurrence, and for repeated event names, the data of the later event wins
+/// (an event without data keeps the existing data). An existing value that is
+/// not a valid list of events or JSON object is overwritten.
+///
 /// Note: An `HxResponseTrigger` implements `IntoResponseParts` and should be
 /// used before any other response object would consume the response parts.
 #[derive(Debug, Clone)]
@@ -153,6 +310,47 @@ impl HxResponseTrigger {
     pub fn after_swap<T: Into<HxEvent>>(events: impl IntoIterator<Item = T>) -> Self {
         Self::new(TriggerMode::AfterSwap, events)
     }
+
+    /// Decodes the value of an `HX-Trigger*` header into a trigger with the
+    /// given mode, see [`TriggerMode::from_header_name`].
+    ///
+    /// Events are kept in the order they appear in the header.
+    ///
+    /// The whole detail of an event is decoded as its data, so the target of
+    /// an event is found in the `target` key of its data, and
+    /// [`HxEvent::target`] is left empty. A target set with
+    /// [`HxEvent::with_target`] cannot be told apart from a `target` key of
+    /// the data itself.
+    pub fn from_header_value(
+        mode: TriggerMode,
+        value: &http::HeaderValue,
+    ) -> Result<Self, HxDecodeError> {
+        events_from_header_value(value).map(|events| Self { mode, events })
+    }
+}
+
+impl HxResponseTrigger {
+    /// Inserts the events into `headers`, merging them with the events of an
+    /// existing header for the same mode.
+    ///
+    /// Existing values that cannot be decoded are overwritten.
+    pub(crate) fn insert_into(self, headers: &mut http::HeaderMap) {
+        if self.events.is_empty() {
+            return;
+        }
+
+        let name = self.mode.header_name();
+
+        let mut existing = Vec::new();
+        for value in headers.get_all(&name) {
+            if let Ok(events) = events_from_header_value(value) {
+                existing.extend(events);
+            }
+        }
+        let events = merge_events(existing, self.events);
+
+        headers.insert(name, events_to_header_value(events));
+    }
 }
 
 impl<T> From<(TriggerMode, T)> for HxResponseTrigger
@@ -169,19 +367,141 @@ where
 }
 
 impl IntoResponseParts for HxResponseTrigger {
-    type Error = HxError;
+    type Error = Infallible;
 
     fn into_response_parts(self, mut res: ResponseParts) -> Result<ResponseParts, Self::Error> {
-        if !self.events.is_empty() {
-            let header = match self.mode {
-                TriggerMode::Normal => headers::HX_TRIGGER,
-                TriggerMode::AfterSettle => headers::HX_TRIGGER_AFTER_SETTLE,
-                TriggerMode::AfterSwap => headers::HX_TRIGGER_AFTER_SETTLE,
-            };
+        self.insert_into(res.headers_mut());
+
+        Ok(res)
+    }
+}
 
-            res.headers_mut()
-                .insert(header, events_to_header_value(self.events)?);
+/// The `HX-Trigger`, `HX-Trigger-After-Swap` and `HX-Trigger-After-Settle`
+/// headers.
+///
+/// Collects events for every phase of the swap and writes each phase to its
+/// own header, unlike multiple [`HxResponseTrigger`] values of the same mode,
+/// which overwrite each other.
+///
+/// Characters that are not ASCII are escaped as `\uXXXX` in the JSON value,
+/// so events may carry any data and names.
+///
+/// ```rust
+/// use axum_htmx::HxTriggers;
+///
+/// async fn save() -> (HxTriggers, &'static str) {
+///     let triggers = HxTriggers::new()
+///         .normal("saved")
+///         .after_swap("highlight")
+///         .after_settle("focus-next");
+///
+///     (triggers, "Saved!")
+/// }
+/// ```
+///
+/// See <https://htmx.org/headers/hx-trigger/> for more information.
+#[derive(Debug, Clone, Default)]
+pub struct HxTriggers {
+    normal: Vec<HxEvent>,
+    after_swap: Vec<HxEvent>,
+    after_settle: Vec<HxEvent>,
+}
+
+impl HxTriggers {
+    /// Creates an empty set of triggers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an event triggered as soon as the response is received.
+    pub fn normal(mut self, event: impl Into<HxEvent>) -> Self {
+        self.push(TriggerMode::Normal, event);
+        self
+    }
+
+    /// Adds an event triggered after the swap step.
+    pub fn after_swap(mut self, event: impl Into<HxEvent>) -> Self {
+        self.push(TriggerMode::AfterSwap, event);
+        self
+    }
+
+    /// Adds an event triggered after the settle step.
+    pub fn after_settle(mut self, event: impl Into<HxEvent>) -> Self {
+        self.push(TriggerMode::AfterSettle, event);
+        self
+    }
+
+    /// Adds an event triggered at the given phase.
+    pub fn push(&mut self, mode: TriggerMode, event: impl Into<HxEvent>) {
+        self.events_mut(mode).push(event.into());
+    }
+
+    /// Returns the events triggered at the given phase.
+    pub fn events(&self, mode: TriggerMode) -> &[HxEvent] {
+        match mode {
+            TriggerMode::Normal => &self.normal,
+            TriggerMode::AfterSwap => &self.after_swap,
+            TriggerMode::AfterSettle => &self.after_settle,
+        }
+    }
+
+    /// Returns `true` if no events are triggered at any phase.
+    pub fn is_empty(&self) -> bool {
+        self.normal.is_empty() && self.after_swap.is_empty() && self.after_settle.is_empty()
+    }
+
+    /// Inserts the events of every phase into `headers`, see
+    /// [`HxResponseTrigger::insert_into`].
+    pub(crate) fn insert_into(self, headers: &mut http::HeaderMap) {
+        let Self {
+            normal,
+            after_swap,
+            after_settle,
+        } = self;
+
+        HxResponseTrigger::normal(normal).insert_into(headers);
+        HxResponseTrigger::after_swap(after_swap).insert_into(headers);
+        HxResponseTrigger::after_settle(after_settle).insert_into(headers);
+    }
+
+    fn events_mut(&mut self, mode: TriggerMode) -> &mut Vec<HxEvent> {
+        match mode {
+            TriggerMode::Normal => &mut self.normal,
+            TriggerMode::AfterSwap => &mut self.after_swap,
+            TriggerMode::AfterSettle => &mut self.after_settle,
         }
+    }
+}
+
+impl From<HxResponseTrigger> for HxTriggers {
+    fn from(trigger: HxResponseTrigger) -> Self {
+        let mut triggers = Self::new();
+        triggers.extend([trigger]);
+        triggers
+    }
+}
+
+impl Extend<HxResponseTrigger> for HxTriggers {
+    fn extend<T: IntoIterator<Item = HxResponseTrigger>>(&mut self, iter: T) {
+        for trigger in iter {
+            self.events_mut(trigger.mode).extend(trigger.events);
+        }
+    }
+}
+
+impl FromIterator<HxResponseTrigger> for HxTriggers {
+    fn from_iter<T: IntoIterator<Item = HxResponseTrigger>>(iter: T) -> Self {
+        let mut triggers = Self::new();
+        triggers.extend(iter);
+        triggers
+    }
+}
+
+impl IntoResponseParts for HxTriggers {
+    type Error = Infallible;
+
+    fn into_response_parts(self, mut res: ResponseParts) -> Result<ResponseParts, Self::Error> {
+        self.insert_into(res.headers_mut());
 
         Ok(res)
     }
@@ -206,14 +526,332 @@ mod tests {
         )
         .unwrap();
 
-        let header_value = events_to_header_value(vec![evt]).unwrap();
+        let header_value = events_to_header_value(vec![evt]);
 
         let expected_value = r#"{"my-event":{"level":"info","message":{"body":"This is a test message.","title":"Hello, world!"}}}"#;
 
         assert_eq!(header_value, HeaderValue::from_static(expected_value));
 
-        let value =
-            events_to_header_value(HxResponseTrigger::normal(["foo", "bar"]).events).unwrap();
+        let value = events_to_header_value(HxResponseTrigger::normal(["foo", "bar"]).events);
         assert_eq!(value, HeaderValue::from_static("foo, bar"));
     }
+
+    #[test]
+    fn non_ascii_events() {
+        let events = vec![
+            HxEvent::new_with_data("saved", json!({"message": "Café saved"})).unwrap(),
+            HxEvent::new_with_json("toast", "✓ done"),
+        ];
+        let value = events_to_header_value(events);
+        assert_eq!(
+            value,
+            r#"{"saved":{"message":"Caf\u00e9 saved"},"toast":"\u2713 done"}"#
+        );
+
+        let events = events_from_header_value(&value).unwrap();
+        let data: serde_json::Value =
+            serde_json::from_str(events[0].data.as_ref().unwrap().get()).unwrap();
+        assert_eq!(data, json!({"message": "Café saved"}));
+
+        let value = events_to_header_value(vec![HxEvent::new("réservé"), HxEvent::new("foo")]);
+        assert_eq!(value, r#"{"r\u00e9serv\u00e9":null,"foo":null}"#);
+    }
+
+    #[test]
+    fn header_to_event_decoding() {
+        let trigger = HxResponseTrigger::from_header_value(
+            TriggerMode::AfterSettle,
+            &HeaderValue::from_static("foo, bar ,baz"),
+        )
+        .unwrap();
+        assert_eq!(trigger.mode, TriggerMode::AfterSettle);
+        let names: Vec<_> = trigger.events.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["foo", "bar", "baz"]);
+
+        let events = events_from_header_value(&HeaderValue::from_static(
+            r#"{"second":null,"first":{"level":"info"}}"#,
+        ))
+        .unwrap();
+        assert_eq!(events[0].name, "second");
+        assert!(events[0].data.is_none());
+        assert_eq!(events[1].name, "first");
+        assert_eq!(
+            events[1].data.as_ref().map(RawJson::get),
+            Some(r#"{"level":"info"}"#)
+        );
+
+        assert!(events_from_header_value(&HeaderValue::from_static("{")).is_err());
+        assert_eq!(
+            TriggerMode::from_header_name(&headers::HX_TRIGGER_AFTER_SWAP),
+            Some(TriggerMode::AfterSwap)
+        );
+    }
+
+    #[test]
+    fn triggers_per_phase() {
+        use axum_core::response::{IntoResponse, Response};
+
+        fn response(parts: impl IntoResponseParts<Error = Infallible>) -> Response {
+            (parts, ()).into_response()
+        }
+
+        let triggers = HxTriggers::new()
+            .normal("saved")
+            .after_swap("swapped")
+            .after_settle(HxEvent::new_with_data("settled", json!({"id": 1})).unwrap())
+            .normal("notified");
+        let res = response(triggers);
+
+        assert_eq!(res.headers()[headers::HX_TRIGGER], "saved, notified");
+        assert_eq!(res.headers()[headers::HX_TRIGGER_AFTER_SWAP], "swapped");
+        assert_eq!(
+            res.headers()[headers::HX_TRIGGER_AFTER_SETTLE],
+            r#"{"settled":{"id":1}}"#
+        );
+
+        for mode in [
+            TriggerMode::Normal,
+            TriggerMode::AfterSwap,
+            TriggerMode::AfterSettle,
+        ] {
+            let res = response(HxResponseTrigger::new(mode, ["event"]));
+            assert_eq!(res.headers().len(), 1);
+            assert_eq!(res.headers()[mode.header_name()], "event");
+
+            let res = response(HxTriggers::from_iter([
+                HxResponseTrigger::new(mode, ["a"]),
+                HxResponseTrigger::new(mode, ["b"]),
+            ]));
+            assert_eq!(res.headers().len(), 1);
+            assert_eq!(res.headers()[mode.header_name()], "a, b");
+        }
+
+        assert!(response(HxTriggers::new()).headers().is_empty());
+    }
+    #[test]
+    fn merge_existing_triggers() {
+        use axum_core::response::IntoResponse;
+
+        let res = (
+            HxResponseTrigger::normal(["first", "second"]),
+            HxResponseTrigger::normal([
+                HxEvent::new_with_data("second", json!({"id": 2})).unwrap(),
+                HxEvent::new("third"),
+            ]),
+            HxResponseTrigger::normal(["second", "first"]),
+            HxResponseTrigger::after_swap(["swapped"]),
+            (),
+        )
+            .into_response();
+
+        assert_eq!(
+            res.headers()[headers::HX_TRIGGER],
+            r#"{"first":null,"second":{"id":2},"third":null}"#
+        );
+        assert_eq!(res.headers()[headers::HX_TRIGGER_AFTER_SWAP], "swapped");
+
+        let res = (
+            HxResponseTrigger::normal(["a", "b", "a"]),
+            HxResponseTrigger::normal(["c", "b"]),
+            (),
+        )
+            .into_response();
+        assert_eq!(res.headers()[headers::HX_TRIGGER], "a, b, c");
+
+        let res = (
+            [(headers::HX_TRIGGER, "{not json")],
+            HxResponseTrigger::normal(["saved"]),
+            (),
+        )
+            .into_response();
+        assert_eq!(res.status(), http::StatusCode::OK);
+        assert_eq!(res.headers()[headers::HX_TRIGGER], "saved");
+    }
+
+    #[test]
+    fn ordered_raw_events() {
+        let raw = RawValue::from_string(r#"{ "z": 1, "a": [2, 3] }"#.to_string()).unwrap();
+        let events = vec![
+            HxEvent::new("zebra"),
+            HxEvent::new_with_raw_data("apple", raw),
+            HxEvent::new_with_data("mango", json!(null)).unwrap(),
+            HxEvent::new_with_data("kiwi", "ripe").unwrap(),
+        ];
+
+        let value = events_to_header_value(events);
+        assert_eq!(
+            value,
+            r#"{"zebra":null,"apple":{ "z": 1, "a": [2, 3] },"mango":null,"kiwi":"ripe"}"#
+        );
+    }
+
+    #[test]
+    fn targeted_events() {
+        let raw = |json: &str| RawValue::from_string(json.to_string()).unwrap();
+        let events = vec![
+            HxEvent::new("refresh").with_target("#cart"),
+            HxEvent::new_with_raw_data("saved", raw(r#"{"id": 1}"#)).with_target("#list"),
+            HxEvent::new_with_raw_data("empty", raw("{ }")).with_target(r#"[name="a"]"#),
+            HxEvent::new_with_data("count", 3)
+                .unwrap()
+                .with_target("#badge"),
+        ];
+
+        let value = events_to_header_value(events);
+        assert_eq!(
+            value,
+            r##"{"refresh":{"target":"#cart"},"saved":{"id": 1,"target":"#list"},"empty":{"target":"[name=\"a\"]"},"count":{"value":3,"target":"#badge"}}"##
+        );
+
+        let events = events_from_header_value(&value).unwrap();
+        let decoded: Vec<_> = events
+            .iter()
+            .map(|e| {
+                (
+                    e.name.as_str(),
+                    e.data.as_ref().map(RawJson::get),
+                    e.target.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            decoded,
+            [
+                ("refresh", Some(r##"{"target":"#cart"}"##), None),
+                ("saved", Some(r##"{"id": 1,"target":"#list"}"##), None),
+                ("empty", Some(r#"{"target":"[name=\"a\"]"}"#), None),
+                ("count", Some(r##"{"value":3,"target":"#badge"}"##), None),
+            ]
+        );
+        // Encoding the decoded events again gives the same header.
+        assert_eq!(events_to_header_value(events), value);
+    }
+
+    #[test]
+    fn colliding_targets() {
+        let event = HxEvent::new_with_data("saved", json!({"target": "a", "id": 1}))
+            .unwrap()
+            .with_target("#list");
+        let value = events_to_header_value(vec![event]);
+        assert_eq!(
+            value,
+            r##"{"saved":{"value":{"id":1,"target":"a"},"target":"#list"}}"##
+        );
+
+        let value = HeaderValue::from_static(r#"{"saved":{"target":"a"}}"#);
+        let events = events_from_header_value(&value).unwrap();
+        assert_eq!(
+            events[0].data.as_ref().map(RawJson::get),
+            Some(r#"{"target":"a"}"#)
+        );
+        assert!(events[0].target.is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "macros")]
+    fn derived_events() {
+        use serde::Serialize;
+
+        #[derive(Serialize, crate::HxEvent)]
+        struct CartUpdated {
+            count: u32,
+        }
+
+        #[derive(crate::HxEvent)]
+        struct HTMLLoaded;
+
+        // Not exported, so the fields don't need to implement `HxShape`.
+        #[derive(Serialize, crate::HxEvent)]
+        struct Connected {
+            peer: std::net::IpAddr,
+        }
+
+        #[derive(crate::HxEvent)]
+        #[allow(dead_code)]
+        enum Toast {
+            Saved,
+            #[hx_event(name = "toast:error")]
+            Failed {
+                #[serde(rename = "msg")]
+                message: String,
+            },
+            Progress(u8),
+            Moved(u32, u32),
+        }
+
+        let events: Vec<HxEvent> = vec![
+            CartUpdated { count: 3 }.into(),
+            HTMLLoaded.into(),
+            Connected {
+                peer: [127, 0, 0, 1].into(),
+            }
+            .into(),
+            Toast::Saved.into(),
+            Toast::Failed {
+                message: "oops".into(),
+            }
+            .into(),
+            Toast::Progress(50).into(),
+            Toast::Moved(1, 2).into(),
+        ];
+
+        assert_eq!(
+            events_to_header_value(events),
+            r#"{"cart-updated":{"count":3},"html-loaded":null,"connected":{"peer":"127.0.0.1"},"saved":null,"toast:error":{"msg":"oops"},"progress":50,"moved":[1,2]}"#
+        );
+
+        let trigger = HxResponseTrigger::normal([CartUpdated { count: 1 }]);
+        assert_eq!(trigger.events[0].name, "cart-updated");
+    }
+
+    #[test]
+    #[cfg(feature = "macros")]
+    fn derived_event_data() {
+        use std::collections::BTreeMap;
+
+        use serde::Serialize;
+
+        #[derive(crate::HxEvent)]
+        #[serde(rename_all_fields = "camelCase")]
+        enum Cart {
+            Updated {
+                item_count: u32,
+            },
+            #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+            Emptied {
+                item_count: u32,
+            },
+            Tagged(BTreeMap<(u8, u8), u8>),
+        }
+
+        // `Tagged` may fail to serialize, so `Cart` only implements `TryFrom`.
+        let events: Vec<HxEvent> = vec![
+            Cart::Updated { item_count: 2 }.try_into().unwrap(),
+            Cart::Emptied { item_count: 0 }.try_into().unwrap(),
+        ];
+        assert_eq!(
+            events_to_header_value(events),
+            r#"{"updated":{"itemCount":2},"emptied":{"ITEM_COUNT":0}}"#
+        );
+
+        let tags = BTreeMap::from([((1, 2), 3)]);
+        assert!(HxEvent::try_from(Cart::Tagged(tags.clone())).is_err());
+        assert!(Cart::Tagged(tags).try_into_hx_event().is_err());
+
+        #[derive(Serialize, crate::HxEvent)]
+        struct Opened {
+            item: Item,
+        }
+
+        #[derive(Serialize)]
+        struct Item {
+            id: u32,
+        }
+
+        let event = HxEvent::try_from(Opened {
+            item: Item { id: 7 },
+        })
+        .unwrap();
+        assert_eq!(event.data.unwrap().get(), r#"{"item":{"id":7}}"#);
+    }
 }
@@ -0,0 +1,410 @@
+//! [`headers::Header`] implementations for the htmx request and response
+//! headers.
+//!
+//! This allows the extractors and responders of this crate to be used with
+//! `axum_extra::TypedHeader`, [`HeaderMapExt`](headers::HeaderMapExt) and any
+//! HTTP client built on top of the `headers` crate, sharing the same encoding
+//! and decoding of the htmx headers.
+//!
+//! As each `HX-Trigger*` response header needs a distinct type,
+//! [`HxResponseTrigger`] is represented by [`HxTriggerEvents`],
+//! [`HxTriggerAfterSettleEvents`] and [`HxTriggerAfterSwapEvents`] here.
+//!
+//! Values that cannot be represented as a header value (e.g. a url with
+//! non-ASCII characters) are not encoded.
+//!
+//! ```rust
+//! use axum_htmx::{HxRetarget, HxTarget};
+//! use headers::HeaderMapExt;
+//! use http::HeaderMap;
+//!
+//! let mut headers = HeaderMap::new();
+//! headers.typed_insert(HxRetarget::from("#main"));
+//! assert_eq!(headers["hx-retarget"], "#main");
+//!
+//! headers.insert("hx-target", "list".parse().unwrap());
+//! let HxTarget(target) = headers.typed_get().unwrap();
+//! assert_eq!(target.as_deref(), Some("list"));
+//! ```
+
+use std::iter;
+
+use headers::{Error, Header};
+use http::{HeaderName, HeaderValue, Uri};
+
+use crate::{
+    HxBoosted, HxCurrentUrl, HxEvent, HxHistoryRestoreRequest, HxLocation, HxPrompt, HxPushUrl,
+    HxRedirect, HxRefresh, HxReplaceUrl, HxRequest, HxReselect, HxResponseTrigger, HxReswap,
+    HxRetarget, HxTarget, HxTrigger, HxTriggerName, TriggerMode,
+    encoding::{decode_header_value, url_header_value},
+    headers as names,
+};
+
+fn first<'i, I>(values: &mut I) -> Result<&'i HeaderValue, Error>
+where
+    I: Iterator<Item = &'i HeaderValue>,
+{
+    values.next().ok_or_else(Error::invalid)
+}
+
+fn encode_bool<E: Extend<HeaderValue>>(value: bool, values: &mut E) {
+    values.extend(iter::once(HeaderValue::from_static(if value {
+        "true"
+    } else {
+        "false"
+    })));
+}
+
+/// Decodes a request header that htmx only sends with the value `true`, as
+/// the extractors of this crate only check for its presence.
+fn decode_flag<'i, I>(values: &mut I) -> Result<bool, Error>
+where
+    I: Iterator<Item = &'i HeaderValue>,
+{
+    Ok(first(values)?.as_bytes() == b"true")
+}
+
+/// Encodes a request header that htmx only sends with the value `true`,
+/// leaving it out for `false` so that the extractors decode it the same way.
+fn encode_flag<E: Extend<HeaderValue>>(value: bool, values: &mut E) {
+    if value {
+        values.extend(iter::once(HeaderValue::from_static("true")));
+    }
+}
+
+fn decode_string<'i, I>(values: &mut I) -> Result<String, Error>
+where
+    I: Iterator<Item = &'i HeaderValue>,
+{
+    decode_header_value(first(values)?, false).map_err(|()| Error::invalid())
+}
+
+fn encode_string<E: Extend<HeaderValue>>(value: &str, values: &mut E) {
+    values.extend(HeaderValue::from_str(value).ok());
+}
+
+macro_rules! impl_header {
+    ($ty:ty, $name:expr, |$values:ident| $decode:expr, |$this:ident, $out:ident| $encode:expr) => {
+        impl Header for $ty {
+            fn name() -> &'static HeaderName {
+                static NAME: HeaderName = $name;
+                &NAME
+            }
+
+            fn decode<'i, I>($values: &mut I) -> Result<Self, Error>
+            where
+                I: Iterator<Item = &'i HeaderValue>,
+            {
+                $decode
+            }
+
+            fn encode<E: Extend<HeaderValue>>(&self, $out: &mut E) {
+                let $this = self;
+                $encode
+            }
+        }
+    };
+}
+
+impl_header!(
+    HxBoosted,
+    names::HX_BOOSTED,
+    |values| decode_flag(values).map(HxBoosted),
+    |this, values| encode_flag(this.0, values)
+);
+
+impl_header!(
+    HxCurrentUrl,
+    names::HX_CURRENT_URL,
+    |values| {
+        let url = decode_string(values)?;
+        url.parse::<Uri>()
+            .map(|url| HxCurrentUrl(Some(url)))
+            .map_err(|_| Error::invalid())
+    },
+    |this, values| {
+        if let Some(url) = &this.0 {
+            encode_string(&url.to_string(), values);
+        }
+    }
+);
+
+impl_header!(
+    HxHistoryRestoreRequest,
+    names::HX_HISTORY_RESTORE_REQUEST,
+    |values| decode_flag(values).map(HxHistoryRestoreRequest),
+    |this, values| encode_flag(this.0, values)
+);
+
+impl_header!(
+    HxPrompt,
+    names::HX_PROMPT,
+    |values| decode_string(values).map(|prompt| HxPrompt(Some(prompt))),
+    |this, values| {
+        if let Some(prompt) = &this.0 {
+            encode_string(prompt, values);
+        }
+    }
+);
+
+impl_header!(
+    HxRequest,
+    names::HX_REQUEST,
+    |values| decode_flag(values).map(HxRequest),
+    |this, values| encode_flag(this.0, values)
+);
+
+impl_header!(
+    HxTarget,
+    names::HX_TARGET,
+    |values| decode_string(values).map(|target| HxTarget(Some(target))),
+    |this, values| {
+        if let Some(target) = &this.0 {
+            encode_string(target, values);
+        }
+    }
+);
+
+impl_header!(
+    HxTriggerName,
+    names::HX_TRIGGER_NAME,
+    |values| decode_string(values).map(|name| HxTriggerName(Some(name))),
+    |this, values| {
+        if let Some(name) = &this.0 {
+            encode_string(name, values);
+        }
+    }
+);
+
+impl_header!(
+    HxTrigger,
+    names::HX_TRIGGER,
+    |values| decode_string(values).map(|trigger| HxTrigger(Some(trigger))),
+    |this, values| {
+        if let Some(trigger) = &this.0 {
+            encode_string(trigger, values);
+        }
+    }
+);
+
+impl_header!(
+    HxLocation,
+    names::HX_LOCATION,
+    |values| HxLocation::try_from(first(values)?).map_err(|_| Error::invalid()),
+    |this, values| encode_string(&this.clone().into_header_with_options(), values)
+);
+
+impl_header!(
+    HxPushUrl,
+    names::HX_PUSH_URL,
+    |values| HxPushUrl::try_from(first(values)?).map_err(|_| Error::invalid()),
+    |this, values| values.extend(iter::once(url_header_value(this.as_str())))
+);
+
+impl_header!(
+    HxRedirect,
+    names::HX_REDIRECT,
+    |values| HxRedirect::try_from(first(values)?).map_err(|_| Error::invalid()),
+    |this, values| values.extend(iter::once(url_header_value(&this.0)))
+);
+
+impl_header!(
+    HxRefresh,
+    names::HX_REFRESH,
+    |values| HxRefresh::try_from(first(values)?).map_err(|_| Error::invalid()),
+    |this, values| encode_bool(this.0, values)
+);
+
+impl_header!(
+    HxReplaceUrl,
+    names::HX_REPLACE_URL,
+    |values| HxReplaceUrl::try_from(first(values)?).map_err(|_| Error::invalid()),
+    |this, values| values.extend(iter::once(url_header_value(this.as_str())))
+);
+
+impl_header!(
+    HxReswap,
+    names::HX_RESWAP,
+    |values| HxReswap::try_from(first(values)?).map_err(|_| Error::invalid()),
+    |this, values| values.extend(iter::once(HeaderValue::from(&this.0)))
+);
+
+impl_header!(
+    HxRetarget,
+    names::HX_RETARGET,
+    |values| HxRetarget::from_header_value(first(values)?).map_err(|_| Error::invalid()),
+    |this, values| encode_string(&this.0, values)
+);
+
+impl_header!(
+    HxReselect,
+    names::HX_RESELECT,
+    |values| HxReselect::from_header_value(first(values)?).map_err(|_| Error::invalid()),
+    |this, values| encode_string(&this.0, values)
+);
+
+macro_rules! trigger_events {
+    ($(#[$docs:meta])* $name:ident, $header:expr, $mode:expr) => {
+        $(#[$docs])*
+        #[derive(Debug, Clone)]
+        pub struct $name(pub Vec<HxEvent>);
+
+        impl_header!(
+            $name,
+            $header,
+            |values| {
+                HxResponseTrigger::from_header_value($mode, first(values)?)
+                    .map(|trigger| $name(trigger.events))
+                    .map_err(|_| Error::invalid())
+            },
+            |this, values| {
+                if !this.0.is_empty() {
+                    values.extend(iter::once(crate::responders::events_to_header_value(this.0.clone())));
+                }
+            }
+        );
+
+        impl From<$name> for HxResponseTrigger {
+            fn from(value: $name) -> Self {
+                HxResponseTrigger::new($mode, value.0)
+            }
+        }
+    };
+}
+
+trigger_events!(
+    /// The events of an `HX-Trigger` response header.
+    ///
+    /// Not to be confused with the `HX-Trigger` request header, see
+    /// [`HxTrigger`].
+    HxTriggerEvents,
+    names::HX_TRIGGER,
+    TriggerMode::Normal
+);
+
+trigger_events!(
+    /// The events of an `HX-Trigger-After-Settle` response header.
+    HxTriggerAfterSettleEvents,
+    names::HX_TRIGGER_AFTER_SETTLE,
+    TriggerMode::AfterSettle
+);
+
+trigger_events!(
+    /// The events of an `HX-Trigger-After-Swap` response header.
+    HxTriggerAfterSwapEvents,
+    names::HX_TRIGGER_AFTER_SWAP,
+    TriggerMode::AfterSwap
+);
+
+#[cfg(test)]
+mod tests {
+    use headers::HeaderMapExt;
+    use http::HeaderMap;
+
+    use std::time::Duration;
+
+    use crate::{Swap, SwapOption};
+
+    use super::*;
+
+    fn round_trip<H: Header>(header: H) -> (HeaderMap, H) {
+        let mut headers = HeaderMap::new();
+        headers.typed_insert(header);
+        let decoded = headers.typed_get().unwrap();
+        (headers, decoded)
+    }
+
+    #[tokio::test]
+    async fn flags_through_extractors() {
+        use axum_core::extract::FromRequestParts;
+
+        for value in [true, false] {
+            let mut request = http::Request::new(());
+            let headers = request.headers_mut();
+            headers.typed_insert(HxRequest(value));
+            headers.typed_insert(HxBoosted(value));
+            headers.typed_insert(HxHistoryRestoreRequest(value));
+            let (mut parts, ()) = request.into_parts();
+
+            let HxRequest(request) = HxRequest::from_request_parts(&mut parts, &())
+                .await
+                .unwrap();
+            let HxBoosted(boosted) = HxBoosted::from_request_parts(&mut parts, &())
+                .await
+                .unwrap();
+            let HxHistoryRestoreRequest(restore) =
+                HxHistoryRestoreRequest::from_request_parts(&mut parts, &())
+                    .await
+                    .unwrap();
+            assert_eq!((request, boosted, restore), (value, value, value));
+        }
+    }
+
+    #[test]
+    fn request_headers() {
+        let (headers, HxRequest(request)) = round_trip(HxRequest(true));
+        assert_eq!(headers[names::HX_REQUEST], "true");
+        assert!(request);
+
+        let mut headers = HeaderMap::new();
+        headers.typed_insert(HxRequest(false));
+        assert!(headers.is_empty());
+        headers.insert(names::HX_BOOSTED, HeaderValue::from_static("false"));
+        assert_eq!(headers.typed_get::<HxBoosted>().map(|h| h.0), Some(false));
+
+        let (headers, HxTarget(target)) = round_trip(HxTarget(Some("main".into())));
+        assert_eq!(headers[names::HX_TARGET], "main");
+        assert_eq!(target.as_deref(), Some("main"));
+
+        let (headers, HxCurrentUrl(url)) = round_trip(HxCurrentUrl(Some(
+            "https://example.com/foo".parse().unwrap(),
+        )));
+        assert_eq!(headers[names::HX_CURRENT_URL], "https://example.com/foo");
+        assert_eq!(url.unwrap().path(), "/foo");
+
+        let mut headers = HeaderMap::new();
+        headers.typed_insert(HxPrompt(None));
+        assert!(headers.is_empty());
+        assert!(headers.typed_get::<HxPrompt>().is_none());
+    }
+
+    #[test]
+    fn response_headers() {
+        let (headers, push) = round_trip(HxPushUrl::url("/foo"));
+        assert_eq!(headers[names::HX_PUSH_URL], "/foo");
+        assert_eq!(push, HxPushUrl::url("/foo"));
+
+        let (headers, replace) = round_trip(HxReplaceUrl::url("/café"));
+        assert_eq!(headers[names::HX_REPLACE_URL], "/caf%C3%A9");
+        assert_eq!(replace, HxReplaceUrl::url("/caf%C3%A9"));
+
+        let (headers, push) = round_trip(HxPushUrl::prevent());
+        assert_eq!(headers[names::HX_PUSH_URL], "false");
+        assert_eq!(push, HxPushUrl::Prevent);
+
+        let (headers, HxRefresh(refresh)) = round_trip(HxRefresh(false));
+        assert_eq!(headers[names::HX_REFRESH], "false");
+        assert!(!refresh);
+
+        let reswap = Swap::new(SwapOption::BeforeEnd).swap(Duration::from_millis(100));
+        let (headers, HxReswap(swap)) = round_trip(HxReswap(reswap.clone()));
+        assert_eq!(headers[names::HX_RESWAP], "beforeend swap:100ms");
+        assert_eq!(swap, reswap);
+
+        let (headers, location) = round_trip(HxLocation::from_str("/foo"));
+        assert_eq!(headers[names::HX_LOCATION], "/foo");
+        assert_eq!(location.uri, "/foo");
+
+        let (headers, HxTriggerAfterSwapEvents(events)) =
+            round_trip(HxTriggerAfterSwapEvents(vec!["foo".into(), "bar".into()]));
+        assert_eq!(headers[names::HX_TRIGGER_AFTER_SWAP], "foo, bar");
+        assert_eq!(events.len(), 2);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            names::HX_RESWAP,
+            HeaderValue::from_static("innerHTML swap:soon"),
+        );
+        assert!(headers.typed_try_get::<HxReswap>().is_err());
+    }
+}
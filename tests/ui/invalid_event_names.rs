@@ -0,0 +1,21 @@
+use axum_htmx::HxEvent;
+
+#[derive(HxEvent)]
+#[hx_event(name = "")]
+struct Empty;
+
+#[derive(HxEvent)]
+#[hx_event(name = "saved,deleted")]
+struct Listed;
+
+#[derive(HxEvent)]
+#[hx_event(name = "café")]
+struct NotAscii;
+
+#[derive(HxEvent)]
+enum Toast {
+    #[hx_event(name = "toast error")]
+    Failed,
+}
+
+fn main() {}
@@ -0,0 +1,19 @@
+use axum_htmx::HxEvent;
+
+#[derive(HxEvent)]
+#[hx_event(name = "toast")]
+enum Toast {
+    Saved,
+}
+
+#[derive(HxEvent)]
+enum Cart {
+    #[hx_event(export)]
+    Updated,
+}
+
+#[derive(HxEvent)]
+#[hx_event(exported)]
+struct Saved;
+
+fn main() {}
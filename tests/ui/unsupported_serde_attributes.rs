@@ -0,0 +1,21 @@
+use axum_htmx::HxEvent;
+
+#[derive(HxEvent)]
+#[serde(rename_all = "camelCase")]
+enum Renamed {
+    Saved { item_id: u32 },
+}
+
+#[derive(HxEvent)]
+#[serde(tag = "type")]
+enum Tagged {
+    Saved { id: u32 },
+}
+
+#[derive(HxEvent)]
+enum Toast {
+    #[serde(rename = "toast")]
+    Shown { message: String },
+}
+
+fn main() {}
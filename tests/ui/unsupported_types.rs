@@ -0,0 +1,14 @@
+use axum_htmx::HxEvent;
+
+#[derive(HxEvent)]
+enum Generic<T> {
+    Saved(T),
+}
+
+#[derive(HxEvent)]
+union Bits {
+    int: u32,
+    float: f32,
+}
+
+fn main() {}